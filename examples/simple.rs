@@ -33,11 +33,12 @@ fn get_model() -> Model {
         "resources/config/config-2.xml",
         6,
         num_cpus::get(),
-    );
+    )
+    .unwrap();
     let limits = (0..graph.num_nodes())
         .map(|id| {
             let node = graph.get_node(GraphNodeId(id));
-            (node.data.0, node.data.1)
+            (node.data.x, node.data.y)
         })
         .fold(
             ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN)),
@@ -101,8 +102,8 @@ fn view(app: &App, model: &Model, frame: Frame) {
         let node = model.graph.get_node(node);
         // map node coordinates to window coordinates
         vec2(
-            (node.data.0 as f32 - (model.limits.0 .0 + model.limits.1 .0) / 2.0) * scale,
-            (node.data.1 as f32 - (model.limits.0 .1 + model.limits.1 .1) / 2.0) * scale,
+            (node.data.x as f32 - (model.limits.0 .0 + model.limits.1 .0) / 2.0) * scale,
+            (node.data.y as f32 - (model.limits.0 .1 + model.limits.1 .1) / 2.0) * scale,
         )
     };
 
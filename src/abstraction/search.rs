@@ -5,10 +5,11 @@ use std::{
     sync::Arc,
 };
 
-use crate::{State, Task, TransitionSystem};
+use crate::{Move, State, Task, TransitionSystem};
 
 /// Defines a time interval (start <= end).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interval<C, DC>
 where
     C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Sub<C, Output = DC> + Copy,
@@ -55,6 +56,7 @@ where
 
 /// Wrapper around an action that also contains the cost of the action.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action<A, DC> {
     /// The action, of None if the agent waits.
     pub action: Option<A>,
@@ -88,6 +90,7 @@ impl<A, DC> Action<A, DC> {
 
 /// Description of a solution to a search problem
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Solution<S, A, C, DC>
 where
     C: Default,
@@ -113,6 +116,34 @@ where
     }
 }
 
+impl<S, A, C, DC> Solution<S, A, C, DC>
+where
+    S: Clone,
+    A: Copy,
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy + Default,
+{
+    /// Returns the timed moves that make up the solution.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent performing the moves.
+    pub fn get_moves(&self, agent: usize) -> Vec<Move<S, A, C, DC>> {
+        self.actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                Move::new(
+                    agent,
+                    self.steps[i].0.clone(),
+                    self.steps[i + 1].0.clone(),
+                    action.action,
+                    Interval::new(self.steps[i].1, self.steps[i + 1].1),
+                )
+            })
+            .collect()
+    }
+}
+
 /// Defines a heuristic function that can be used by a search algorithm,
 /// for a given transition system and task.
 pub trait Heuristic<TS, S, A, C, DC>
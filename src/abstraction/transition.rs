@@ -39,6 +39,11 @@ where
     /// Returns true if agents can wait at the given state.
     fn can_wait_at(&self, state: &S) -> bool;
 
+    /// Returns the maximum time agents can wait at the given state, if any.
+    fn max_waiting_time(&self, _state: &S) -> Option<DC> {
+        None
+    }
+
     /// Returns true if the two moves lead to a collision.
     fn conflict(&self, moves: A2<&Move<S, A, C, DC>>) -> bool;
 }
@@ -56,6 +61,7 @@ impl<Action, X: FnMut(Action)> ActionCallback<Action> for X {
 
 /// Definition of a task in a given transition system that can then
 /// be fed to a search algorithm.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Task<S, C>
 where
     S: State + Eq + Clone,
@@ -106,6 +112,7 @@ where
 
 /// Definition of a move in a transition system.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move<S, A, C, DC>
 where
     C: Ord + LimitValues + Sub<C, Output = DC> + Copy,
@@ -0,0 +1,162 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
+
+use caboose::{find_instances, sweep_agents, BenchmarkResult};
+
+const USAGE: &str = "\
+Usage: benchmark [directories...] [options]
+
+Solves all benchmark instances found in the given directories (default: resources/instances)
+with an increasing number of agents, until the search fails, and writes the results as CSV.
+
+Options:
+    --config <file>       Configuration file (default: resources/config/config-2.xml)
+    --threads <n,...>     Comma-separated numbers of threads to use (default: 1)
+    --agents <n>          Number of agents of the first run (default: 1)
+    --step <n>            Number of agents added after each successful run (default: 1)
+    --max-agents <n>      Maximum number of agents (default: all)
+    --time-limit <secs>   Time limit of each run (default: given by the configuration)
+    --output <file>       Write the results to the given file instead of the standard output
+    -h, --help            Print this message";
+
+/// The command-line arguments.
+struct Args {
+    directories: Vec<PathBuf>,
+    config_file: String,
+    threads: Vec<usize>,
+    min_agents: usize,
+    step: usize,
+    max_agents: usize,
+    time_limit: Option<Duration>,
+    output_file: Option<String>,
+}
+
+/// Parses the given command-line arguments, or returns None if the usage should be printed.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        directories: vec![],
+        config_file: "resources/config/config-2.xml".to_string(),
+        threads: vec![1],
+        min_agents: 1,
+        step: 1,
+        max_agents: usize::MAX,
+        time_limit: None,
+        output_file: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        let number = |value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--config" => parsed.config_file = value()?,
+            "--threads" => {
+                parsed.threads = value()?.split(',').map(number).collect::<Result<_, _>>()?
+            }
+            "--agents" => parsed.min_agents = number(&value()?)?,
+            "--step" => parsed.step = number(&value()?)?,
+            "--max-agents" => parsed.max_agents = number(&value()?)?,
+            "--time-limit" => {
+                let value = value()?;
+                parsed.time_limit = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?,
+                );
+            }
+            "--output" => parsed.output_file = Some(value()?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ => parsed.directories.push(PathBuf::from(arg)),
+        }
+    }
+
+    if parsed.directories.is_empty() {
+        parsed
+            .directories
+            .push(PathBuf::from("resources/instances"));
+    }
+
+    Ok(Some(parsed))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut output: Box<dyn Write> = match &args.output_file {
+        Some(file) => Box::new(File::create(file).map_err(|e| format!("{}: {}", file, e))?),
+        None => Box::new(io::stdout()),
+    };
+    let mut write = |line: &str| {
+        writeln!(output, "{}", line)
+            .and_then(|_| output.flush())
+            .map_err(|e| e.to_string())
+    };
+
+    write(BenchmarkResult::CSV_HEADER)?;
+
+    for directory in args.directories.iter() {
+        let instances =
+            find_instances(directory).map_err(|e| format!("{}: {}", directory.display(), e))?;
+        for instance in instances.iter() {
+            for &n_threads in args.threads.iter() {
+                let agents = (args.min_agents..=args.max_agents).step_by(args.step);
+                let mut result = Ok(());
+                if let Err(e) = sweep_agents(
+                    instance,
+                    &args.config_file,
+                    agents,
+                    n_threads,
+                    args.time_limit,
+                    |record| {
+                        if result.is_ok() {
+                            result = write(&record.to_csv_record());
+                        }
+                    },
+                ) {
+                    // Skip the instances that cannot be parsed
+                    eprintln!("error: {}", e);
+                }
+                result?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => match run(args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Ok(None) => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{env, fs, process::ExitCode, time::Duration};
+
+use caboose::{get_cbs_from_files, to_ccbs_log, CbsStats};
+
+const USAGE: &str = "\
+Usage: caboose <map> <tasks> <config> [options]
+
+Solves a benchmark instance with Continuous Conflict-Based Search.
+The map can be given in the XML format of mapf.info, or in the MovingAI `.map` format.
+
+Options:
+    --agents <n>          Number of agents to consider (default: all)
+    --threads <n>         Number of threads to use (default: number of CPUs)
+    --time-limit <secs>   Time limit of the search (default: given by the configuration)
+    --solution <format>   Print the solution, either as `json` or as a Continuous-CBS `log`
+    --output <file>       Write the solution to the given file instead of the standard output
+    -h, --help            Print this message";
+
+/// The format in which to print the solution.
+enum SolutionFormat {
+    Json,
+    Log,
+}
+
+/// The command-line arguments.
+struct Args {
+    map_file: String,
+    task_file: String,
+    config_file: String,
+    n_agents: usize,
+    n_threads: usize,
+    time_limit: Option<Duration>,
+    solution_format: Option<SolutionFormat>,
+    output_file: Option<String>,
+}
+
+/// Parses the given command-line arguments, or returns None if the usage should be printed.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut files = vec![];
+    let mut n_agents = usize::MAX;
+    let mut n_threads = num_cpus::get();
+    let mut time_limit = None;
+    let mut solution_format = None;
+    let mut output_file = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        let number = |value: String| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid value for {}: {}", arg, value))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--agents" => n_agents = number(value()?)?,
+            "--threads" => n_threads = number(value()?)?.max(1),
+            "--time-limit" => {
+                let value = value()?;
+                time_limit = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?,
+                );
+            }
+            "--solution" => {
+                solution_format = Some(match value()?.as_str() {
+                    "json" => SolutionFormat::Json,
+                    "log" => SolutionFormat::Log,
+                    format => return Err(format!("Unknown solution format: {}", format)),
+                })
+            }
+            "--output" => output_file = Some(value()?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ => files.push(arg),
+        }
+    }
+
+    let [map_file, task_file, config_file]: [String; 3] = files
+        .try_into()
+        .map_err(|_| "Expected a map, a task and a configuration file".to_string())?;
+
+    Ok(Some(Args {
+        map_file,
+        task_file,
+        config_file,
+        n_agents,
+        n_threads,
+        time_limit,
+        solution_format,
+        output_file,
+    }))
+}
+
+fn print_stats(cost: f64, stats: &CbsStats) {
+    println!("cost: {}", cost);
+    println!("runtime: {:.6}s", stats.elapsed.as_secs_f64());
+    println!("high-level expanded: {}", stats.expanded);
+    println!("low-level searches: {}", stats.lsipp_stats.searches);
+    println!(
+        "low-level expanded: {}",
+        stats.lsipp_stats.sipp_stats.expanded
+    );
+    println!(
+        "heuristic queries: {} new, {} cached",
+        stats.rra_stats.new_query, stats.rra_stats.cached_query
+    );
+    println!("heuristic expanded: {}", stats.rra_stats.expanded);
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    let (graph, mut cbs, mut config, _) = get_cbs_from_files(
+        &args.map_file,
+        &args.task_file,
+        &args.config_file,
+        args.n_agents,
+        args.n_threads,
+    )
+    .map_err(|e| e.to_string())?;
+
+    if args.time_limit.is_some() {
+        config.time_limit = args.time_limit;
+    }
+
+    let solutions = cbs.solve(&config);
+    let stats = cbs.get_stats();
+
+    let Some(solutions) = solutions else {
+        println!("no solution found");
+        print_stats(f64::INFINITY, &stats);
+        return Ok(ExitCode::FAILURE);
+    };
+
+    print_stats(solutions.iter().map(|s| s.cost.0).sum(), &stats);
+
+    let contents = match args.solution_format {
+        None => return Ok(ExitCode::SUCCESS),
+        Some(SolutionFormat::Log) => to_ccbs_log(&solutions, &stats, |state| {
+            let data = graph.get_node(state.internal_state.0).data;
+            (data.x, data.y)
+        })
+        .map_err(|e| e.to_string())?,
+        #[cfg(feature = "serde")]
+        Some(SolutionFormat::Json) => {
+            serde_json::to_string_pretty(&solutions).map_err(|e| e.to_string())?
+        }
+        #[cfg(not(feature = "serde"))]
+        Some(SolutionFormat::Json) => {
+            return Err("The JSON format requires the `serde` feature".to_string())
+        }
+    };
+
+    match args.output_file {
+        Some(file) => fs::write(&file, contents).map_err(|e| format!("{}: {}", file, e))?,
+        None => println!("{}", contents),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => run(args).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }),
+        Ok(None) => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -15,7 +15,10 @@ use parking_lot::{Condvar, Mutex};
 use tuple::{A2, T2};
 
 use crate::{
-    search::{Conflict, ConflictType, Constraint, ConstraintSet, ConstraintType, LandmarkSet},
+    search::{
+        find_conflict, Conflict, ConflictType, Constraint, ConstraintSet, ConstraintType,
+        LandmarkSet,
+    },
     Heuristic, HeuristicBuilder, Interval, LSippConfig, LSippStats, LimitValues, Move,
     ReverseResumableAStar, RraStats, SafeIntervalPathPlanningWithLandmarks, SippState, Solution,
     State, Task, TransitionSystem,
@@ -183,10 +186,13 @@ where
                 continue;
             }
 
+            let (constraints, landmarks) =
+                root.get_constraints(agent, &config.external_constraints);
+
             let config = LSippConfig::new_with_pivots(
                 task.clone(),
-                Default::default(),
-                Default::default(),
+                constraints,
+                landmarks,
                 config.pivots.clone(),
                 config.heuristic_to_pivots.clone(),
                 config.precision,
@@ -387,7 +393,7 @@ where
                     }
                 }
 
-                if successor.conflicting_constraints(agents[i]) {
+                if successor.conflicting_constraints(agents[i], &config.external_constraints) {
                     continue;
                 }
 
@@ -420,8 +426,8 @@ where
 
         // Check if the agents are already frozen
         let frozen: T2<bool, bool> = T2(
-            config.frozen.contains_key(&agents[0]),
-            config.frozen.contains_key(&agents[1]),
+            config.is_immovable(agents[0]),
+            config.is_immovable(agents[1]),
         );
 
         // Get one constraint for each agent from the transition system to avoid the conflict
@@ -463,10 +469,10 @@ where
         let constraint_sets = (
             successors[0]
                 .as_ref()
-                .map(|succ| succ.get_constraints(agents[0])),
+                .map(|succ| succ.get_constraints(agents[0], &config.external_constraints)),
             successors[1]
                 .as_ref()
-                .map(|succ| succ.get_constraints(agents[1])),
+                .map(|succ| succ.get_constraints(agents[1], &config.external_constraints)),
         );
 
         // Compute a new path for each agent, taking into account the new constraint
@@ -683,6 +689,18 @@ where
                     conflicts.push(Arc::new(conflict));
                 }
             }
+
+            // Compute conflicts between the given agent and all obstacles
+            for obstacle in 0..config.obstacles.len() {
+                if let Some((conflict, avoidable)) = Self::get_obstacle_conflict(
+                    shared, config, node, &solutions, agent, obstacle, lsipp,
+                ) {
+                    if !avoidable {
+                        return false;
+                    }
+                    conflicts.push(Arc::new(conflict));
+                }
+            }
         } else {
             // Root node, compute conflicts between each pair of solutions
             for i in 0..config.n_agents {
@@ -696,6 +714,21 @@ where
                         conflicts.push(Arc::new(conflict));
                     }
                 }
+
+                if config.frozen.contains_key(&i) {
+                    continue;
+                }
+
+                for obstacle in 0..config.obstacles.len() {
+                    if let Some((conflict, avoidable)) = Self::get_obstacle_conflict(
+                        shared, config, node, &solutions, i, obstacle, lsipp,
+                    ) {
+                        if !avoidable {
+                            return false;
+                        }
+                        conflicts.push(Arc::new(conflict));
+                    }
+                }
             }
         }
 
@@ -720,140 +753,172 @@ where
             ReverseResumableAStar<TS, S, A, C, DC, H>,
         >,
     ) -> Option<(Conflict<S, A, C, DC>, bool)> {
-        let mut conflict = None;
+        let conflict = find_conflict(
+            shared.transition_system.as_ref(),
+            T2(
+                (agents[0], solutions[agents[0]]),
+                (agents[1], solutions[agents[1]]),
+            ),
+        )
+        .filter(|moves| !config.is_ignored(moves))
+        .map(Conflict::new);
 
-        // Iterate through both solutions and find moves overlapping in C
-        let mut index = T2(0, 0);
-        let mut intervals = T2(Interval::default(), Interval::default());
-        loop {
-            // Compute the interval of each move
-            for k in 0..=1 {
-                intervals[k].start = solutions[agents[k]].steps[index[k]].1;
-                intervals[k].end = if index[k] < solutions[agents[k]].actions.len() {
-                    solutions[agents[k]].steps[index[k] + 1].1
-                } else {
-                    C::max_value()
-                };
-            }
+        conflict.map(|conflict| {
+            Self::classify_conflict(shared, config, node, solutions, conflict, lsipp)
+        })
+    }
 
-            // Ignore moves with no duration
-            if intervals[0].start == intervals[0].end {
-                index[0] += 1;
-                continue;
-            } else if intervals[1].start == intervals[1].end {
-                index[1] += 1;
+    /// Returns the first conflict between the solution of the given agent and the given obstacle, if any,
+    /// and whether it can be avoided.
+    #[allow(clippy::too_many_arguments)]
+    fn get_obstacle_conflict(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        solutions: &[&Solution<Arc<SippState<S, C, DC>>, A, C, DC>],
+        agent: usize,
+        obstacle: usize,
+        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
+            TS,
+            S,
+            A,
+            C,
+            DC,
+            ReverseResumableAStar<TS, S, A, C, DC, H>,
+        >,
+    ) -> Option<(Conflict<S, A, C, DC>, bool)> {
+        let solution = solutions[agent];
+
+        let mut index = 0;
+        for obstacle_move in config.obstacles[obstacle].iter() {
+            // The agent ignores the moves it performs itself
+            if obstacle_move.agent == agent {
                 continue;
             }
 
-            // Check if the intervals overlap
-            if intervals[0].overlaps(&intervals[1]) {
-                // Check if the moves lead to a conflict
-                let moves = T2(
-                    Move::new(
-                        agents[0],
-                        solutions[agents[0]].steps[index[0]]
-                            .0
-                            .internal_state
-                            .clone(),
-                        solutions[agents[0]]
-                            .steps
-                            .get(index[0] + 1)
-                            .map(|s| s.0.internal_state.clone())
-                            .unwrap_or(
-                                solutions[agents[0]].steps[index[0]]
-                                    .0
-                                    .internal_state
-                                    .clone(),
-                            ),
-                        solutions[agents[0]]
-                            .actions
-                            .get(index[0])
-                            .and_then(|a| a.action),
-                        intervals[0],
-                    ),
-                    Move::new(
-                        agents[1],
-                        solutions[agents[1]].steps[index[1]]
-                            .0
-                            .internal_state
-                            .clone(),
-                        solutions[agents[1]]
-                            .steps
-                            .get(index[1] + 1)
-                            .map(|s| s.0.internal_state.clone())
-                            .unwrap_or(
-                                solutions[agents[1]].steps[index[1]]
-                                    .0
-                                    .internal_state
-                                    .clone(),
-                            ),
-                        solutions[agents[1]]
-                            .actions
-                            .get(index[1])
-                            .and_then(|a| a.action),
-                        intervals[1],
-                    ),
+            // Skip the moves of the agent that end before the obstacle move
+            while index < solution.actions.len()
+                && solution.steps[index + 1].1 < obstacle_move.interval.start
+            {
+                index += 1;
+            }
+
+            // Check all moves of the agent that overlap with the obstacle move
+            let mut current = index;
+            loop {
+                let interval = Interval::new(
+                    solution.steps[current].1,
+                    if current < solution.actions.len() {
+                        solution.steps[current + 1].1
+                    } else {
+                        C::max_value()
+                    },
                 );
 
-                if shared.transition_system.conflict(T2(&moves.0, &moves.1)) {
-                    conflict = Some(Conflict::new(moves));
+                if interval.start > obstacle_move.interval.end {
                     break;
                 }
-            }
-
-            if index[0] < solutions[agents[0]].actions.len() && intervals[0].end <= intervals[1].end
-            {
-                index[0] += 1;
-            } else if index[1] < solutions[agents[1]].actions.len() {
-                index[1] += 1;
-            } else {
-                break;
-            }
-        }
 
-        if let Some(mut conflict) = conflict {
-            // Determine conflict type by trying to avoid it
-            let (_, new_solutions, _) =
-                Self::get_successors(shared, config, node, &conflict, lsipp);
-
-            if let (None, None) = (&new_solutions[0], &new_solutions[1]) {
-                return Some((conflict, false));
-            } else if let (Some(solution), None) = (&new_solutions[0], &new_solutions[1]) {
-                conflict.overcost = solution.cost - solutions[agents[0]].cost;
-                if config.frozen.contains_key(&agents[1]) {
-                    conflict.type_ = ConflictType::Frozen;
-                } else {
-                    conflict.type_ = ConflictType::Cardinal;
-                }
-            } else if let (None, Some(solution)) = (&new_solutions[0], &new_solutions[1]) {
-                conflict.overcost = solution.cost - solutions[agents[1]].cost;
-                if config.frozen.contains_key(&agents[0]) {
-                    conflict.type_ = ConflictType::Frozen;
-                } else {
-                    conflict.type_ = ConflictType::Cardinal;
+                if interval.start < interval.end && interval.overlaps(&obstacle_move.interval) {
+                    let moves = T2(
+                        Move::new(
+                            agent,
+                            solution.steps[current].0.internal_state.clone(),
+                            solution
+                                .steps
+                                .get(current + 1)
+                                .map(|s| s.0.internal_state.clone())
+                                .unwrap_or(solution.steps[current].0.internal_state.clone()),
+                            solution.actions.get(current).and_then(|a| a.action),
+                            interval,
+                        ),
+                        Move::new(
+                            config.n_agents + obstacle,
+                            obstacle_move.from.clone(),
+                            obstacle_move.to.clone(),
+                            obstacle_move.action,
+                            obstacle_move.interval,
+                        ),
+                    );
+
+                    if !config.is_ignored(&moves)
+                        && shared.transition_system.conflict(T2(&moves.0, &moves.1))
+                    {
+                        return Some(Self::classify_conflict(
+                            shared,
+                            config,
+                            node,
+                            solutions,
+                            Conflict::new(moves),
+                            lsipp,
+                        ));
+                    }
                 }
-            } else if let (Some(solution1), Some(solution2)) =
-                (&new_solutions[0], &new_solutions[1])
-            {
-                let overcost1 = solution1.cost - solutions[agents[0]].cost;
-                let overcost2 = solution2.cost - solutions[agents[1]].cost;
-                if overcost1 > DC::default() && overcost2 > DC::default() {
-                    conflict.overcost = overcost1.min(overcost2);
-                    conflict.type_ = ConflictType::Cardinal;
-                } else if overcost1 > DC::default() || overcost2 > DC::default() {
-                    conflict.overcost = overcost1.max(overcost2);
-                    conflict.type_ = ConflictType::SemiCardinal;
-                } else {
-                    conflict.type_ = ConflictType::NonCardinal;
+
+                if current == solution.actions.len() {
+                    break;
                 }
+                current += 1;
             }
-
-            return Some((conflict, true));
         }
 
         None
     }
 
+    /// Determines the type of the given conflict by trying to avoid it, and returns whether it can be avoided.
+    fn classify_conflict(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        solutions: &[&Solution<Arc<SippState<S, C, DC>>, A, C, DC>],
+        mut conflict: Conflict<S, A, C, DC>,
+        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
+            TS,
+            S,
+            A,
+            C,
+            DC,
+            ReverseResumableAStar<TS, S, A, C, DC, H>,
+        >,
+    ) -> (Conflict<S, A, C, DC>, bool) {
+        let agents = T2(conflict.moves.0.agent, conflict.moves.1.agent);
+
+        // Determine conflict type by trying to avoid it
+        let (_, new_solutions, _) = Self::get_successors(shared, config, node, &conflict, lsipp);
+
+        if let (None, None) = (&new_solutions[0], &new_solutions[1]) {
+            return (conflict, false);
+        } else if let (Some(solution), None) = (&new_solutions[0], &new_solutions[1]) {
+            conflict.overcost = solution.cost - solutions[agents[0]].cost;
+            if config.is_immovable(agents[1]) {
+                conflict.type_ = ConflictType::Frozen;
+            } else {
+                conflict.type_ = ConflictType::Cardinal;
+            }
+        } else if let (None, Some(solution)) = (&new_solutions[0], &new_solutions[1]) {
+            conflict.overcost = solution.cost - solutions[agents[1]].cost;
+            if config.is_immovable(agents[0]) {
+                conflict.type_ = ConflictType::Frozen;
+            } else {
+                conflict.type_ = ConflictType::Cardinal;
+            }
+        } else if let (Some(solution1), Some(solution2)) = (&new_solutions[0], &new_solutions[1]) {
+            let overcost1 = solution1.cost - solutions[agents[0]].cost;
+            let overcost2 = solution2.cost - solutions[agents[1]].cost;
+            if overcost1 > DC::default() && overcost2 > DC::default() {
+                conflict.overcost = overcost1.min(overcost2);
+                conflict.type_ = ConflictType::Cardinal;
+            } else if overcost1 > DC::default() || overcost2 > DC::default() {
+                conflict.overcost = overcost1.max(overcost2);
+                conflict.type_ = ConflictType::SemiCardinal;
+            } else {
+                conflict.type_ = ConflictType::NonCardinal;
+            }
+        }
+
+        (conflict, true)
+    }
+
     /// Returns the statistics of the search algorithm.
     pub fn get_stats(&mut self) -> CbsStats {
         self.shared.critical.lock().stats
@@ -873,7 +938,7 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Copy,
+    DC: Copy + Ord,
     H: Heuristic<TS, S, A, C, DC>,
 {
     /// The number of agents to consider.
@@ -882,6 +947,10 @@ where
     pub tasks: Vec<Arc<Task<S, C>>>,
     /// A set of frozen agents and their already planned paths.
     frozen: FxHashMap<usize, Solution<Arc<SippState<S, C, DC>>, A, C, DC>>,
+    /// A set of moving obstacles, given by their timed moves.
+    obstacles: Vec<Vec<Move<S, A, C, DC>>>,
+    /// The constraints imposed by the obstacles and the closures, to all agents or a given agent.
+    external_constraints: Vec<Arc<Constraint<S, C, DC>>>,
     /// A set of pivot states.
     pivots: Arc<Vec<S>>,
     /// A set of heuristics to those pivot states.
@@ -892,6 +961,10 @@ where
     n_threads: usize,
     /// The time limit for the search.
     pub time_limit: Option<Duration>,
+    /// The end of the window in which conflicts are resolved, or None to resolve all conflicts.
+    /// Conflicts starting at or after this time are ignored, so that the returned paths
+    /// are only guaranteed to be conflict-free before the horizon.
+    pub horizon: Option<C>,
     _phantom: PhantomData<(TS, A)>,
 }
 
@@ -907,7 +980,7 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Copy,
+    DC: Copy + Ord,
     H: Heuristic<TS, S, A, C, DC>,
 {
     /// Creates a new configuration for the Conflict-Based Search algorithm.
@@ -946,11 +1019,14 @@ where
             n_agents: tasks.len(),
             tasks,
             frozen: FxHashMap::default(),
+            obstacles: vec![],
+            external_constraints: vec![],
             pivots,
             heuristic_to_pivots,
             precision,
             n_threads,
             time_limit,
+            horizon: None,
             _phantom: PhantomData,
         }
     }
@@ -977,16 +1053,20 @@ where
             n_agents: tasks.len(),
             tasks,
             frozen: FxHashMap::default(),
+            obstacles: vec![],
+            external_constraints: vec![],
             pivots,
             heuristic_to_pivots,
             precision,
             n_threads,
             time_limit,
+            horizon: None,
             _phantom: PhantomData,
         }
     }
 
     /// Adds a frozen agent and its already planned path to the configuration.
+    /// Conflicts between two frozen agents cannot be resolved, and are therefore ignored.
     ///
     /// # Arguments
     ///
@@ -999,6 +1079,113 @@ where
     ) {
         self.frozen.insert(agent, solution);
     }
+
+    /// Adds a moving obstacle that all agents must avoid, given by its timed trajectory.
+    /// The obstacle is only considered between the first and the last step of the trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `trajectory` - The trajectory followed by the obstacle
+    pub fn add_obstacle(&mut self, trajectory: &Solution<S, A, C, DC>)
+    where
+        A: Copy,
+    {
+        self.add_obstacle_moves(trajectory.get_moves(usize::MAX));
+    }
+
+    /// Adds a moving obstacle that all agents must avoid, given by its timed moves.
+    /// The obstacle is only considered during the intervals of the given moves.
+    /// A move (other than a wait) whose agent is one of the planned agents is ignored by that agent,
+    /// which allows describing a move that the agent is currently performing.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - The moves performed by the obstacle
+    pub fn add_obstacle_moves(&mut self, mut moves: Vec<Move<S, A, C, DC>>) {
+        moves.retain(|m| m.interval.start < m.interval.end);
+        moves.sort_unstable_by_key(|m| m.interval.start);
+
+        // Agents can never be at the same state as a waiting obstacle
+        for m in moves.iter().filter(|m| m.action.is_none()) {
+            self.external_constraints
+                .push(Arc::new(Constraint::new_state_constraint(
+                    usize::MAX,
+                    m.from.clone(),
+                    m.interval,
+                )));
+        }
+
+        self.obstacles.push(moves);
+    }
+
+    /// Closes the given state during the given interval, for all agents or for the given ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to close
+    /// * `interval` - The interval during which no agent can be at the state
+    /// * `agents` - The agents concerned by the closure, or None for all agents
+    pub fn add_state_closure(
+        &mut self,
+        state: S,
+        interval: Interval<C, DC>,
+        agents: Option<&[usize]>,
+    ) {
+        for agent in agents.unwrap_or(&[usize::MAX]) {
+            self.external_constraints
+                .push(Arc::new(Constraint::new_state_constraint(
+                    *agent,
+                    state.clone(),
+                    interval,
+                )));
+        }
+    }
+
+    /// Closes the action connecting the two given states during the given interval,
+    /// for all agents or for the given ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The state the action goes from
+    /// * `to` - The state the action goes to
+    /// * `interval` - The interval during which no agent can perform the action
+    /// * `agents` - The agents concerned by the closure, or None for all agents
+    pub fn add_action_closure(
+        &mut self,
+        from: S,
+        to: S,
+        interval: Interval<C, DC>,
+        agents: Option<&[usize]>,
+    ) {
+        for agent in agents.unwrap_or(&[usize::MAX]) {
+            self.external_constraints
+                .push(Arc::new(Constraint::new_closure(
+                    *agent,
+                    from.clone(),
+                    to.clone(),
+                    interval,
+                )));
+        }
+    }
+
+    /// Adds a constraint imposed by the environment, to all agents or to a given agent.
+    pub(crate) fn add_external_constraint(&mut self, constraint: Arc<Constraint<S, C, DC>>) {
+        self.external_constraints.push(constraint);
+    }
+
+    /// Returns true if the given agent cannot be replanned, i.e. it is either frozen or an obstacle.
+    fn is_immovable(&self, agent: usize) -> bool {
+        agent >= self.n_agents || self.frozen.contains_key(&agent)
+    }
+
+    /// Returns true if the given conflicting moves should be ignored, either because of the horizon,
+    /// or because none of the agents involved can be replanned.
+    fn is_ignored(&self, moves: &A2<Move<S, A, C, DC>>) -> bool {
+        (self.is_immovable(moves.0.agent) && self.is_immovable(moves.1.agent))
+            || self.horizon.is_some_and(|horizon| {
+                moves.0.interval.start.max(moves.1.interval.start) >= horizon
+            })
+    }
 }
 
 /// A node in the Conflict-Based Search tree.
@@ -1066,10 +1253,17 @@ where
     pub fn get_constraints(
         &self,
         agent: usize,
+        external_constraints: &[Arc<Constraint<S, C, DC>>],
     ) -> (Arc<ConstraintSet<S, C, DC>>, LandmarkSet<S, C, DC>) {
         let mut constraints = ConstraintSet::default();
         let mut landmarks = LandmarkSet::default();
 
+        for constraint in external_constraints {
+            if constraint.agent == usize::MAX || constraint.agent == agent {
+                constraints.add(constraint);
+            }
+        }
+
         let mut current = self;
         loop {
             if let Some(constraint) = &current.constraint {
@@ -1100,10 +1294,17 @@ where
     pub fn get_constraints_alt(
         &self,
         agent: usize,
+        external_constraints: &[Arc<Constraint<S, C, DC>>],
     ) -> (ConstraintSet<S, C, DC>, Vec<A2<Arc<Constraint<S, C, DC>>>>) {
         let mut constraints = ConstraintSet::default();
         let mut landmarks = vec![];
 
+        for constraint in external_constraints {
+            if constraint.agent == usize::MAX || constraint.agent == agent {
+                constraints.add(constraint);
+            }
+        }
+
         let mut current = self;
         loop {
             if let Some(constraint) = &current.constraint {
@@ -1196,8 +1397,12 @@ where
         false
     }
 
-    fn conflicting_constraints(&self, agent: usize) -> bool {
-        let (constraints, landmarks) = self.get_constraints_alt(agent);
+    fn conflicting_constraints(
+        &self,
+        agent: usize,
+        external_constraints: &[Arc<Constraint<S, C, DC>>],
+    ) -> bool {
+        let (constraints, landmarks) = self.get_constraints_alt(agent, external_constraints);
 
         for landmark in landmarks.iter() {
             if let Some(constraint_set) =
@@ -1284,6 +1489,7 @@ where
 
 /// Statistics of the Conflict-Based Search algorithm.
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CbsStats {
     /// The number of CBS nodes expanded.
     pub expanded: usize,
@@ -1302,7 +1508,8 @@ mod tests {
     use ordered_float::OrderedFloat;
 
     use crate::{
-        simple_graph, GraphEdgeId, GraphNodeId, SimpleHeuristic, SimpleState, SimpleWorld, Task,
+        simple_graph, Action, GraphEdgeId, GraphNodeId, Interval, Move, SimpleHeuristic,
+        SimpleState, SimpleWorld, Solution, Task,
     };
 
     use super::{CbsConfig, ConflictBasedSearch};
@@ -1354,6 +1561,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_horizon() {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        let tasks = vec![
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(9)),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(9)),
+                SimpleState(GraphNodeId(0)),
+                OrderedFloat(0.0),
+            )),
+        ];
+
+        let mut config: CbsConfig<_, _, GraphEdgeId, _, _, SimpleHeuristic> = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        let mut solver = ConflictBasedSearch::new(transition_system);
+
+        // The agents meet in the middle of the corridor, after the horizon
+        config.horizon = Some(OrderedFloat(4.0));
+        let solutions = solver.solve(&config).unwrap();
+        assert_eq!(solutions[0].cost + solutions[1].cost, OrderedFloat(18.0));
+
+        config.horizon = Some(OrderedFloat(20.0));
+        let solutions = solver.solve(&config).unwrap();
+        assert_eq!(solutions[0].cost + solutions[1].cost, OrderedFloat(20.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::{CbsStats, MyTime, SippState};
+
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        let tasks = vec![
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(9)),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(9)),
+                SimpleState(GraphNodeId(0)),
+                OrderedFloat(0.0),
+            )),
+        ];
+
+        // Tasks can be persisted and restored
+        let json = serde_json::to_string(&tasks).unwrap();
+        let tasks: Vec<Arc<Task<SimpleState, OrderedFloat<f64>>>> =
+            serde_json::from_str(&json).unwrap();
+
+        let config: CbsConfig<_, _, GraphEdgeId, _, _, SimpleHeuristic> = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        let mut solver = ConflictBasedSearch::new(transition_system);
+        let solutions = solver.solve(&config).unwrap();
+
+        let json = serde_json::to_string(&solutions).unwrap();
+        let restored: Vec<
+            Solution<Arc<SippState<SimpleState, MyTime, MyTime>>, GraphEdgeId, MyTime, MyTime>,
+        > = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", restored), format!("{:?}", solutions));
+
+        let stats = solver.get_stats();
+        let json = serde_json::to_string(&stats).unwrap();
+        let restored: CbsStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.expanded, stats.expanded);
+        assert_eq!(restored.elapsed, stats.elapsed);
+    }
+
     #[test]
     fn test_frozen() {
         let size = 10;
@@ -1409,4 +1698,141 @@ mod tests {
 
         assert_eq!(solutions[0].steps, config.frozen[&0].steps);
     }
+
+    #[test]
+    fn test_obstacles() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph.clone(), 0.4));
+
+        let tasks = vec![Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(9)),
+            OrderedFloat(0.0),
+        ))];
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+
+        // An obstacle waiting on the shortest path
+        let mut config: CbsConfig<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = CbsConfig::new(
+            transition_system.clone(),
+            tasks.clone(),
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        config.add_obstacle_moves(vec![Move::new(
+            0,
+            SimpleState(GraphNodeId(5)),
+            SimpleState(GraphNodeId(5)),
+            None,
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+        )]);
+
+        let solutions = solver.solve(&config).unwrap();
+
+        assert_eq!(solutions[0].cost, OrderedFloat(11.0));
+
+        // An obstacle moving onto the shortest path and waiting there
+        let edge = *graph
+            .get_edges_out(GraphNodeId(15))
+            .iter()
+            .find(|edge| graph.get_edge(**edge).to == GraphNodeId(5))
+            .unwrap();
+        let trajectory = Solution {
+            cost: OrderedFloat(20.0),
+            steps: vec![
+                (SimpleState(GraphNodeId(15)), OrderedFloat(0.0)),
+                (SimpleState(GraphNodeId(15)), OrderedFloat(4.0)),
+                (SimpleState(GraphNodeId(5)), OrderedFloat(5.0)),
+                (SimpleState(GraphNodeId(5)), OrderedFloat(20.0)),
+            ],
+            actions: vec![
+                Action::wait(OrderedFloat(4.0)),
+                Action::new(edge, OrderedFloat(1.0)),
+                Action::wait(OrderedFloat(15.0)),
+            ],
+        };
+
+        let mut config = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        config.add_obstacle(&trajectory);
+
+        let solutions = solver.solve(&config).unwrap();
+
+        assert_eq!(solutions[0].cost, OrderedFloat(11.0));
+    }
+
+    #[test]
+    fn test_closures() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = vec![
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(9)),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(30)),
+                SimpleState(GraphNodeId(39)),
+                OrderedFloat(0.0),
+            )),
+        ];
+
+        let mut config: CbsConfig<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+
+        // Only the first agent is concerned by this closure
+        config.add_state_closure(
+            SimpleState(GraphNodeId(35)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+            Some(&[0]),
+        );
+        // Both agents are concerned by these closures
+        config.add_state_closure(
+            SimpleState(GraphNodeId(5)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+            None,
+        );
+        config.add_action_closure(
+            SimpleState(GraphNodeId(33)),
+            SimpleState(GraphNodeId(34)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+            None,
+        );
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+
+        let solutions = solver.solve(&config).unwrap();
+
+        assert_eq!(solutions[0].cost, OrderedFloat(11.0));
+        assert_eq!(solutions[1].cost, OrderedFloat(11.0));
+    }
 }
@@ -1,35 +1,91 @@
-use std::path::PathBuf;
+use std::{f64::consts::PI, path::PathBuf};
 
-use crate::{get_cbs_from_files, MyTime};
+use ordered_float::OrderedFloat;
 
-pub fn solve(map_file: &str, task_file: &str, config_file: &str, n_agents: usize) -> MyTime {
+use crate::{
+    get_cbs_from_files, get_heading_cbs_from_files, validate_solutions, MyTime, SimpleWorld,
+};
+
+fn get_files(map_file: &str, task_file: &str, config_file: &str) -> (String, String, String) {
     let map = PathBuf::new()
         .join(env!("CARGO_MANIFEST_DIR"))
         .join("resources/instances")
         .join(map_file)
         .join("map.xml");
-    let map = map.to_str().unwrap();
 
     let task = PathBuf::new()
         .join(env!("CARGO_MANIFEST_DIR"))
         .join("resources/instances")
         .join(map_file)
         .join(task_file);
-    let task = task.to_str().unwrap();
 
     let config = PathBuf::new()
         .join(env!("CARGO_MANIFEST_DIR"))
         .join("resources/config")
         .join(config_file);
-    let config = config.to_str().unwrap();
 
-    let (_, mut cbs, config, _) = get_cbs_from_files(&map, &task, &config, n_agents, 1);
+    (
+        map.to_str().unwrap().to_string(),
+        task.to_str().unwrap().to_string(),
+        config.to_str().unwrap().to_string(),
+    )
+}
+
+pub fn solve(map_file: &str, task_file: &str, config_file: &str, n_agents: usize) -> MyTime {
+    let (map, task, config) = get_files(map_file, task_file, config_file);
+
+    let (graph, mut cbs, config, agent_size) =
+        get_cbs_from_files(&map, &task, &config, n_agents, 1).unwrap();
+
+    let solution = cbs.solve(&config).unwrap();
+
+    let transition_system = SimpleWorld::new(graph, agent_size);
+    let violations = validate_solutions(
+        &transition_system,
+        &config.tasks,
+        &solution,
+        OrderedFloat(1e-6),
+    );
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    solution.iter().map(|sol| sol.cost).sum()
+}
+
+pub fn solve_with_headings(
+    map_file: &str,
+    task_file: &str,
+    config_file: &str,
+    n_agents: usize,
+    angular_speed: f64,
+) -> MyTime {
+    let (map, task, config) = get_files(map_file, task_file, config_file);
+
+    let (_, mut cbs, config, _) =
+        get_heading_cbs_from_files(&map, &task, &config, n_agents, 1, angular_speed, 0.0).unwrap();
 
     let solution = cbs.solve(&config).unwrap();
 
     solution.iter().map(|sol| sol.cost).sum()
 }
 
+#[test]
+fn empty_16_16_random_empty_16_16_random_1_5_with_headings() {
+    let without_headings = solve(
+        "empty-16-16-random",
+        "empty-16-16-random-1.xml",
+        "config-2.xml",
+        5,
+    );
+    let with_headings = solve_with_headings(
+        "empty-16-16-random",
+        "empty-16-16-random-1.xml",
+        "config-2.xml",
+        5,
+        PI / 2.0,
+    );
+    assert!(with_headings >= without_headings);
+}
+
 #[test]
 fn den520d_random_den520d_random_1_9() {
     assert!(
@@ -0,0 +1,274 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    ops::{Add, Sub},
+};
+
+/// Enumerates the assignments of the rows of a cost matrix to distinct columns
+/// by non-decreasing total cost, using Murty's algorithm. Missing costs denote forbidden pairs.
+/// The matrix must have at least as many columns as rows.
+pub(crate) struct Assignments<DC> {
+    costs: Vec<Vec<Option<DC>>>,
+    queue: BinaryHeap<AssignmentNode<DC>>,
+}
+
+impl<DC> Assignments<DC>
+where
+    DC: Ord + Add<DC, Output = DC> + Sub<DC, Output = DC> + Copy + Default,
+{
+    /// Creates a new enumeration of the assignments of the given cost matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `costs` - The cost of assigning each row to each column, if allowed.
+    pub fn new(costs: Vec<Vec<Option<DC>>>) -> Self {
+        let mut assignments = Self {
+            costs,
+            queue: BinaryHeap::new(),
+        };
+        assignments.push(vec![], vec![]);
+        assignments
+    }
+
+    /// Solves the assignment problem with the given constraints and queues its solution, if any.
+    fn push(&mut self, forced: Vec<(usize, usize)>, forbidden: Vec<(usize, usize)>) {
+        let mut costs = self.costs.clone();
+        for (row, column) in &forbidden {
+            costs[*row][*column] = None;
+        }
+        for (row, column) in &forced {
+            for (other, cost) in costs[*row].iter_mut().enumerate() {
+                if other != *column {
+                    *cost = None;
+                }
+            }
+            for (other, costs) in costs.iter_mut().enumerate() {
+                if other != *row {
+                    costs[*column] = None;
+                }
+            }
+        }
+
+        if let Some((cost, assignment)) = solve_assignment(&costs) {
+            self.queue.push(AssignmentNode {
+                cost,
+                assignment,
+                forced,
+                forbidden,
+            });
+        }
+    }
+}
+
+impl<DC> Iterator for Assignments<DC>
+where
+    DC: Ord + Add<DC, Output = DC> + Sub<DC, Output = DC> + Copy + Default,
+{
+    type Item = (DC, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop()?;
+
+        // Partition the remaining assignments: the i-th subproblem keeps the first i - 1 free pairs
+        // of the current assignment and forbids the i-th one
+        let free = (0..node.assignment.len())
+            .filter(|row| node.forced.iter().all(|(r, _)| r != row))
+            .collect::<Vec<_>>();
+        let mut forced = node.forced.clone();
+        for row in free {
+            let mut forbidden = node.forbidden.clone();
+            forbidden.push((row, node.assignment[row]));
+            self.push(forced.clone(), forbidden);
+            forced.push((row, node.assignment[row]));
+        }
+
+        Some((node.cost, node.assignment))
+    }
+}
+
+/// A subproblem of Murty's algorithm, with its optimal assignment.
+struct AssignmentNode<DC> {
+    cost: DC,
+    assignment: Vec<usize>,
+    forced: Vec<(usize, usize)>,
+    forbidden: Vec<(usize, usize)>,
+}
+
+impl<DC> PartialEq for AssignmentNode<DC>
+where
+    DC: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl<DC> Eq for AssignmentNode<DC> where DC: Ord {}
+
+impl<DC> PartialOrd for AssignmentNode<DC>
+where
+    DC: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<DC> Ord for AssignmentNode<DC>
+where
+    DC: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost) // Inverted to get a min-heap
+    }
+}
+
+/// Returns the minimum cost assignment of the rows of the given matrix to distinct columns,
+/// with its cost, using the Hungarian algorithm. Returns None if no assignment exists.
+///
+/// # Arguments
+///
+/// * `costs` - The cost of assigning each row to each column, if allowed.
+pub(crate) fn solve_assignment<DC>(costs: &[Vec<Option<DC>>]) -> Option<(DC, Vec<usize>)>
+where
+    DC: Ord + Add<DC, Output = DC> + Sub<DC, Output = DC> + Copy + Default,
+{
+    let n = costs.len();
+    let m = costs.first().map_or(0, |row| row.len());
+    if n > m {
+        return None;
+    }
+
+    // Potentials of the rows and columns, with index 0 used as a sentinel
+    let mut u = vec![DC::default(); n + 1];
+    let mut v = vec![DC::default(); m + 1];
+    let mut row_of = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_reduced: Vec<Option<DC>> = vec![None; m + 1];
+        let mut used = vec![false; m + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = None;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                if let Some(cost) = costs[i0 - 1][j - 1] {
+                    let reduced = cost - u[i0] - v[j];
+                    if min_reduced[j].is_none_or(|min| reduced < min) {
+                        min_reduced[j] = Some(reduced);
+                        way[j] = j0;
+                    }
+                }
+                if let Some(min) = min_reduced[j] {
+                    if delta.is_none_or(|delta| min < delta) {
+                        delta = Some(min);
+                        j1 = j;
+                    }
+                }
+            }
+
+            // No column can be reached from the current rows
+            let delta = delta?;
+
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] = u[row_of[j]] + delta;
+                    v[j] = v[j] - delta;
+                } else if let Some(min) = min_reduced[j] {
+                    min_reduced[j] = Some(min - delta);
+                }
+            }
+
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+
+        // Augment along the alternating path
+        loop {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=m {
+        if row_of[j] > 0 {
+            assignment[row_of[j] - 1] = j - 1;
+        }
+    }
+    let cost = assignment
+        .iter()
+        .enumerate()
+        .fold(DC::default(), |cost, (row, column)| {
+            cost + costs[row][*column].unwrap()
+        });
+
+    Some((cost, assignment))
+}
+
+#[cfg(test)]
+mod tests {
+    use fxhash::FxHashSet;
+
+    use super::{solve_assignment, Assignments};
+
+    #[test]
+    fn test_assignment() {
+        let costs = vec![
+            vec![Some(4), Some(1), Some(3)],
+            vec![Some(2), Some(0), Some(5)],
+            vec![Some(3), Some(2), Some(2)],
+        ];
+        assert_eq!(solve_assignment(&costs), Some((5, vec![1, 0, 2])));
+
+        // Rectangular matrix with forbidden pairs
+        let costs = vec![vec![Some(1), None, Some(7)], vec![Some(2), None, Some(9)]];
+        assert_eq!(solve_assignment(&costs), Some((9, vec![2, 0])));
+
+        let costs = vec![vec![Some(1), None], vec![Some(2), None]];
+        assert_eq!(solve_assignment(&costs), None);
+    }
+
+    #[test]
+    fn test_murty() {
+        let costs = vec![
+            vec![Some(4), Some(1), Some(3)],
+            vec![Some(2), Some(0), Some(5)],
+            vec![Some(3), Some(2), Some(2)],
+        ];
+
+        let assignments = Assignments::new(costs.clone()).collect::<Vec<_>>();
+        assert_eq!(assignments.len(), 6);
+        assert!(assignments.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(
+            assignments
+                .iter()
+                .map(|(_, a)| a.clone())
+                .collect::<FxHashSet<_>>()
+                .len(),
+            6
+        );
+        for (cost, assignment) in &assignments {
+            let expected: i32 = assignment
+                .iter()
+                .enumerate()
+                .map(|(row, column)| costs[row][*column].unwrap())
+                .sum();
+            assert_eq!(*cost, expected);
+        }
+    }
+}
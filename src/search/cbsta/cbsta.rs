@@ -0,0 +1,415 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Div, Sub},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    search::cbsta::assignment::Assignments, CbsConfig, ConflictBasedSearch, Heuristic,
+    HeuristicBuilder, HeuristicCache, LimitValues, SippState, Solution, State, Task,
+    TransitionSystem,
+};
+
+/// Implementation of Conflict-Based Search with Task Assignment (CBS-TA), which jointly
+/// assigns goals to agents and finds collision-free paths, minimizing the sum of costs.
+/// The assignments are enumerated by non-decreasing sum of the individual distances of the agents
+/// to their goals, computed with RRA*, and each one is solved with Conflict-Based Search.
+/// The search stops as soon as the next assignment cannot improve on the best solution found.
+pub struct CbsTa<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC> + Send + Sync,
+    S: Debug + State + Eq + Hash + Clone + Send + Sync,
+    A: Debug + Copy + Send + Sync,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues
+        + Send
+        + Sync,
+    DC: Debug
+        + Hash
+        + Ord
+        + Add<DC, Output = DC>
+        + Sub<DC, Output = DC>
+        + Div<f64, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
+    H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC> + Send + Sync,
+{
+    transition_system: Arc<TS>,
+    solver: ConflictBasedSearch<TS, S, A, C, DC, H>,
+    stats: CbsTaStats,
+}
+
+impl<TS, S, A, C, DC, H> CbsTa<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC> + Send + Sync,
+    S: Debug + State + Eq + Hash + Clone + Send + Sync,
+    A: Debug + Copy + Send + Sync,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues
+        + Send
+        + Sync,
+    DC: Debug
+        + Hash
+        + Ord
+        + Add<DC, Output = DC>
+        + Sub<DC, Output = DC>
+        + Div<f64, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
+    H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC> + Send + Sync,
+{
+    /// Creates a new instance of the CBS-TA algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition_system` - The transition system in which the agents navigate.
+    pub fn new(transition_system: Arc<TS>) -> Self {
+        Self {
+            solver: ConflictBasedSearch::new(transition_system.clone()),
+            transition_system,
+            stats: CbsTaStats::default(),
+        }
+    }
+
+    /// Attempts to solve the given configuration, and returns the optimal assignment
+    /// with the corresponding solutions. Returns None if no feasible assignment exists,
+    /// or if the time limit is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration of the problem.
+    pub fn solve(
+        &mut self,
+        config: &CbsTaConfig<S, C, DC>,
+    ) -> Option<CbsTaSolution<Arc<SippState<S, C, DC>>, A, C, DC>> {
+        let start = Instant::now();
+        self.stats = CbsTaStats::default();
+
+        let n_agents = config.initial_states.len();
+        let n_goals = config.goals.len();
+        let mut heuristics =
+            HeuristicCache::new(self.transition_system.clone(), n_agents + n_goals);
+
+        let get_task = |agent: usize, goal: Option<usize>| {
+            let initial_state = &config.initial_states[agent];
+            Arc::new(Task::new(
+                initial_state.clone(),
+                goal.map_or(initial_state, |goal| &config.goals[goal])
+                    .clone(),
+                config.initial_cost,
+            ))
+        };
+
+        // The rows of the assignment problem are the smallest set among agents and goals
+        let goals_as_rows = n_goals <= n_agents;
+        let mut distances = vec![vec![None; n_goals]; n_agents];
+        for (agent, distances) in distances.iter_mut().enumerate() {
+            for (goal, distance) in distances.iter_mut().enumerate() {
+                if config.is_eligible(agent, goal) {
+                    *distance = heuristics
+                        .get(&get_task(agent, Some(goal)))
+                        .get_heuristic(&config.initial_states[agent]);
+                }
+            }
+        }
+        let costs = if goals_as_rows {
+            (0..n_goals)
+                .map(|goal| (0..n_agents).map(|agent| distances[agent][goal]).collect())
+                .collect()
+        } else {
+            distances
+        };
+
+        let mut best: Option<CbsTaSolution<Arc<SippState<S, C, DC>>, A, C, DC>> = None;
+        for (lower_bound, rows) in Assignments::new(costs) {
+            if best.as_ref().is_some_and(|best| lower_bound >= best.cost) {
+                break;
+            }
+
+            let time_limit = match config.time_limit {
+                Some(time_limit) => match time_limit.checked_sub(start.elapsed()) {
+                    Some(remaining) => Some(remaining),
+                    None => return self.timeout(start),
+                },
+                None => None,
+            };
+
+            let mut assignment = vec![None; n_agents];
+            for (row, column) in rows.iter().enumerate() {
+                if goals_as_rows {
+                    assignment[*column] = Some(row);
+                } else {
+                    assignment[row] = Some(*column);
+                }
+            }
+
+            let tasks = (0..n_agents)
+                .map(|agent| get_task(agent, assignment[agent]))
+                .collect::<Vec<_>>();
+            let heuristic_to_pivots = tasks
+                .iter()
+                .map(|task| heuristics.get(task))
+                .collect::<Vec<_>>();
+            let cbs_config = CbsConfig::new_with_pivots(
+                tasks.clone(),
+                Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect()),
+                Arc::new(heuristic_to_pivots),
+                config.precision,
+                config.n_threads,
+                time_limit,
+            );
+
+            self.stats.assignments += 1;
+            let solutions = self.solver.solve(&cbs_config);
+            self.stats.expanded += self.solver.get_stats().expanded;
+
+            match solutions {
+                Some(solutions) => {
+                    let cost = solutions.iter().fold(DC::default(), |cost, solution| {
+                        cost + (solution.cost - config.initial_cost)
+                    });
+                    if best.as_ref().is_none_or(|best| cost < best.cost) {
+                        best = Some(CbsTaSolution {
+                            cost,
+                            assignment,
+                            solutions,
+                        });
+                    }
+                }
+                None => {
+                    if config
+                        .time_limit
+                        .is_some_and(|time_limit| start.elapsed() >= time_limit)
+                    {
+                        return self.timeout(start);
+                    }
+                }
+            }
+        }
+
+        self.stats.elapsed = start.elapsed();
+        best
+    }
+
+    fn timeout(
+        &mut self,
+        start: Instant,
+    ) -> Option<CbsTaSolution<Arc<SippState<S, C, DC>>, A, C, DC>> {
+        self.stats.elapsed = start.elapsed();
+        None
+    }
+
+    /// Returns the statistics of the last search.
+    pub fn get_stats(&self) -> CbsTaStats {
+        self.stats
+    }
+}
+
+/// Input configuration for the CBS-TA algorithm.
+pub struct CbsTaConfig<S, C, DC>
+where
+    S: State + Eq + Clone,
+    C: Copy,
+    DC: Copy,
+{
+    /// The initial state of each agent.
+    pub initial_states: Vec<S>,
+    /// The goals to assign to the agents. Each goal is assigned to at most one agent,
+    /// and each agent receives at most one goal. If there are fewer goals than agents,
+    /// every goal is assigned and the remaining agents stay at their initial state.
+    /// Otherwise, every agent receives a goal.
+    pub goals: Vec<S>,
+    /// Whether each agent can be assigned each goal, all pairs being allowed if None.
+    pub eligibility: Option<Vec<Vec<bool>>>,
+    /// The initial cost of the agents.
+    pub initial_cost: C,
+    /// The precision used when detecting collisions.
+    pub precision: DC,
+    /// The number of threads to use.
+    pub n_threads: usize,
+    /// The time limit of the whole search.
+    pub time_limit: Option<Duration>,
+}
+
+impl<S, C, DC> CbsTaConfig<S, C, DC>
+where
+    S: State + Eq + Clone,
+    C: Copy,
+    DC: Copy,
+{
+    /// Creates a new configuration in which every agent can be assigned every goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_states` - The initial state of each agent.
+    /// * `goals` - The goals to assign to the agents.
+    /// * `initial_cost` - The initial cost of the agents.
+    /// * `precision` - The precision used when detecting collisions.
+    /// * `n_threads` - The number of threads to use.
+    /// * `time_limit` - The time limit of the whole search.
+    pub fn new(
+        initial_states: Vec<S>,
+        goals: Vec<S>,
+        initial_cost: C,
+        precision: DC,
+        n_threads: usize,
+        time_limit: Option<Duration>,
+    ) -> Self {
+        Self {
+            initial_states,
+            goals,
+            eligibility: None,
+            initial_cost,
+            precision,
+            n_threads,
+            time_limit,
+        }
+    }
+
+    /// Returns true if the given agent can be assigned the given goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent.
+    /// * `goal` - The index of the goal.
+    pub fn is_eligible(&self, agent: usize, goal: usize) -> bool {
+        self.eligibility
+            .as_ref()
+            .is_none_or(|eligibility| eligibility[agent][goal])
+    }
+}
+
+/// The solution found by the CBS-TA algorithm.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbsTaSolution<S, A, C, DC>
+where
+    C: Default,
+{
+    /// The sum of costs of the solutions.
+    pub cost: DC,
+    /// The goal assigned to each agent, or None if the agent stays at its initial state.
+    pub assignment: Vec<Option<usize>>,
+    /// The solution of each agent.
+    pub solutions: Vec<Solution<S, A, C, DC>>,
+}
+
+/// Statistics of the CBS-TA algorithm.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbsTaStats {
+    /// The number of assignments solved with Conflict-Based Search.
+    pub assignments: usize,
+    /// The total number of CBS nodes expanded.
+    pub expanded: usize,
+    /// The time elapsed during the search.
+    pub elapsed: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, CbsTa, CbsTaConfig, GraphNodeId, SimpleHeuristic, SimpleState, SimpleWorld,
+    };
+
+    fn solve(
+        config: &CbsTaConfig<SimpleState, OrderedFloat<f64>, OrderedFloat<f64>>,
+    ) -> Option<(f64, Vec<Option<usize>>)> {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        let mut solver = CbsTa::<_, _, _, _, _, SimpleHeuristic>::new(transition_system);
+        solver.solve(config).map(|solution| {
+            for (agent, goal) in solution.assignment.iter().enumerate() {
+                let state = &solution.solutions[agent]
+                    .steps
+                    .last()
+                    .unwrap()
+                    .0
+                    .internal_state;
+                match goal {
+                    Some(goal) => assert_eq!(state, &config.goals[*goal]),
+                    None => assert_eq!(state, &config.initial_states[agent]),
+                }
+            }
+            (solution.cost.0, solution.assignment)
+        })
+    }
+
+    fn states(nodes: &[usize]) -> Vec<SimpleState> {
+        nodes.iter().map(|n| SimpleState(GraphNodeId(*n))).collect()
+    }
+
+    #[test]
+    fn test_assignment() {
+        let mut config = CbsTaConfig::new(
+            states(&[0, 90]),
+            states(&[99, 9]),
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        assert_eq!(solve(&config), Some((18.0, vec![Some(1), Some(0)])));
+
+        // The nearest goals are not eligible
+        config.eligibility = Some(vec![vec![true, false], vec![false, true]]);
+        let (cost, assignment) = solve(&config).unwrap();
+        assert!(cost >= 36.0);
+        assert_eq!(assignment, vec![Some(0), Some(1)]);
+
+        config.eligibility = Some(vec![vec![false, false], vec![false, true]]);
+        assert_eq!(solve(&config), None);
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        // More agents than goals
+        let config = CbsTaConfig::new(
+            states(&[0, 55, 99]),
+            states(&[90]),
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        assert_eq!(solve(&config), Some((9.0, vec![Some(0), None, None])));
+
+        // More goals than agents
+        let config = CbsTaConfig::new(
+            states(&[0]),
+            states(&[99, 55, 3]),
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        assert_eq!(solve(&config), Some((3.0, vec![Some(2)])));
+    }
+}
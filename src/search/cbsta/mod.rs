@@ -0,0 +1,4 @@
+mod assignment;
+mod cbsta;
+
+pub use cbsta::*;
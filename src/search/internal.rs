@@ -167,15 +167,19 @@ where
 
 /// The types of constraints that can be imposed on agents in a search algorithm.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstraintType {
     /// Constraint that prevents an agent from visiting the given state during a given interval.
     State,
     /// Constraint that prevents an agent from connecting the two given states during a given interval.
     Action,
+    /// Constraint that prevents an agent from being between the two given states at any time during a given interval.
+    Closure,
 }
 
 /// Defines a constraint that can be imposed on a given agent in a search algorithm.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constraint<S, C, DC>
 where
     C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Sub<C, Output = DC> + Copy,
@@ -216,6 +220,15 @@ where
             type_: ConstraintType::Action,
         }
     }
+    pub fn new_closure(agent: usize, state: S, next: S, interval: Interval<C, DC>) -> Self {
+        Self {
+            agent,
+            state,
+            next: Some(next),
+            interval,
+            type_: ConstraintType::Closure,
+        }
+    }
 }
 
 impl<S, C, DC> PartialEq for Constraint<S, C, DC>
@@ -265,6 +278,7 @@ where
 {
     pub state_constraints: FxHashMap<S, Vec<Constraint<S, C, DC>>>,
     pub action_constraints: FxHashMap<(S, S), Vec<Constraint<S, C, DC>>>,
+    pub action_closures: FxHashMap<(S, S), Vec<Constraint<S, C, DC>>>,
 }
 
 impl<S, C, DC> Default for ConstraintSet<S, C, DC>
@@ -277,6 +291,7 @@ where
         Self {
             state_constraints: Default::default(),
             action_constraints: Default::default(),
+            action_closures: Default::default(),
         }
     }
 }
@@ -304,6 +319,15 @@ where
                     .or_default()
                     .push(constraint.as_ref().clone());
             }
+            ConstraintType::Closure => {
+                self.action_closures
+                    .entry((
+                        constraint.state.clone(),
+                        constraint.next.as_ref().unwrap().clone(),
+                    ))
+                    .or_default()
+                    .push(constraint.as_ref().clone());
+            }
         }
     }
 
@@ -315,57 +339,79 @@ where
         self.action_constraints.get(&(from.clone(), to.clone()))
     }
 
+    pub fn get_action_closures(&self, from: &S, to: &S) -> Option<&Vec<Constraint<S, C, DC>>> {
+        self.action_closures.get(&(from.clone(), to.clone()))
+    }
+
     pub fn unify(&mut self) {
-        for constraints in self.state_constraints.values_mut() {
-            constraints.sort_unstable();
-
-            let mut unified_constraints = vec![];
-
-            let mut i = 0;
-            while i < constraints.len() {
-                let mut constraint = constraints[i].clone();
-
-                let mut j = i + 1;
-                while j < constraints.len()
-                    && constraint.interval.overlaps(&constraints[j].interval)
-                {
-                    constraint.interval.end =
-                        constraint.interval.end.max(constraints[j].interval.end);
-                    j += 1;
-                }
-
-                unified_constraints.push(constraint);
-                i = j + 1;
-            }
+        self.state_constraints.values_mut().for_each(unify);
+        self.action_constraints.values_mut().for_each(unify);
+        self.action_closures.values_mut().for_each(unify);
+    }
+}
 
-            *constraints = unified_constraints;
+/// Sorts the given constraints and merges those with overlapping intervals.
+pub fn unify<S, C, DC>(constraints: &mut Vec<Constraint<S, C, DC>>)
+where
+    S: Clone,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Copy + Sub<C, Output = DC>,
+    DC: PartialEq + Eq + PartialOrd + Ord + Copy,
+{
+    constraints.sort_unstable();
+
+    let mut unified_constraints = vec![];
+
+    let mut i = 0;
+    while i < constraints.len() {
+        let mut constraint = constraints[i].clone();
+
+        let mut j = i + 1;
+        while j < constraints.len() && constraint.interval.overlaps(&constraints[j].interval) {
+            constraint.interval.end = constraint.interval.end.max(constraints[j].interval.end);
+            j += 1;
         }
 
-        for constraints in self.action_constraints.values_mut() {
-            constraints.sort_unstable();
+        unified_constraints.push(constraint);
+        i = j;
+    }
 
-            let mut unified_constraints = vec![];
+    *constraints = unified_constraints;
+}
+
+pub type LandmarkSet<S, C, DC> = Vec<Arc<Constraint<S, C, DC>>>;
 
-            let mut i = 0;
-            while i < constraints.len() {
-                let mut constraint = constraints[i].clone();
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
-                let mut j = i + 1;
-                while j < constraints.len()
-                    && constraint.interval.overlaps(&constraints[j].interval)
-                {
-                    constraint.interval.end =
-                        constraint.interval.end.max(constraints[j].interval.end);
-                    j += 1;
-                }
+    use ordered_float::OrderedFloat;
 
-                unified_constraints.push(constraint);
-                i = j + 1;
-            }
+    use crate::{GraphNodeId, Interval, SimpleState};
 
-            *constraints = unified_constraints;
+    use super::{Constraint, ConstraintSet};
+
+    #[test]
+    fn test_unify() {
+        let state = SimpleState(GraphNodeId(0));
+        let interval = |start: f64, end: f64| Interval::new(OrderedFloat(start), OrderedFloat(end));
+
+        let mut constraints = ConstraintSet::default();
+        for interval in [interval(0.0, 2.0), interval(1.0, 3.0), interval(5.0, 6.0)] {
+            constraints.add(&Arc::new(Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                interval,
+            )));
         }
+        constraints.unify();
+
+        // The disjoint interval that follows the merged ones is kept
+        let unified = constraints
+            .get_state_constraints(&state)
+            .unwrap()
+            .iter()
+            .map(|c| c.interval)
+            .collect::<Vec<_>>();
+        assert_eq!(unified, vec![interval(0.0, 3.0), interval(5.0, 6.0)]);
     }
 }
-
-pub type LandmarkSet<S, C, DC> = Vec<Arc<Constraint<S, C, DC>>>;
@@ -0,0 +1,209 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Sub},
+    sync::Arc,
+};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    Heuristic, HeuristicBuilder, LimitValues, ReverseResumableAStar, State, Task, TransitionSystem,
+};
+
+/// A bounded cache of Reverse Resumable A* instances indexed by their goal state,
+/// which allows reusing the distances already computed to a goal across agents and requests.
+/// When the cache is full, the least recently used instance is evicted.
+pub struct HeuristicCache<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + State + Hash + Eq + Clone,
+    C: Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Copy,
+    H: Heuristic<TS, S, A, C, DC>,
+{
+    transition_system: Arc<TS>,
+    capacity: usize,
+    heuristics: FxHashMap<S, (Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>, usize)>,
+    last_use: usize,
+}
+
+impl<TS, S, A, C, DC, H> HeuristicCache<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + State + Hash + Eq + Clone,
+    C: Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Copy,
+    H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC>,
+{
+    /// Creates a new cache for the given transition system.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition_system` - The transition system in which the agents navigate.
+    /// * `capacity` - The maximum number of instances kept in the cache.
+    pub fn new(transition_system: Arc<TS>, capacity: usize) -> Self {
+        Self {
+            transition_system,
+            capacity,
+            heuristics: FxHashMap::default(),
+            last_use: 0,
+        }
+    }
+
+    /// Returns the instance computing the distances to the goal of the given task,
+    /// which is created if it is not in the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task whose goal is the target of the heuristic.
+    pub fn get(
+        &mut self,
+        task: &Arc<Task<S, C>>,
+    ) -> Arc<ReverseResumableAStar<TS, S, A, C, DC, H>> {
+        self.last_use += 1;
+        if let Some((heuristic, last_use)) = self.heuristics.get_mut(&task.goal_state) {
+            *last_use = self.last_use;
+            return heuristic.clone();
+        }
+
+        let heuristic = Arc::new(ReverseResumableAStar::new(
+            self.transition_system.clone(),
+            task.clone(),
+            H::build(self.transition_system.clone(), Arc::new(task.reverse())),
+        ));
+        if self.capacity > 0 {
+            self.heuristics
+                .insert(task.goal_state.clone(), (heuristic.clone(), self.last_use));
+            self.evict();
+        }
+        heuristic
+    }
+
+    /// Returns the instance computing the distances to the goal of the given task if it is in the cache,
+    /// without marking it as recently used, or a new instance that is not added to the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task whose goal is the target of the heuristic.
+    pub fn peek(&self, task: &Arc<Task<S, C>>) -> Arc<ReverseResumableAStar<TS, S, A, C, DC, H>> {
+        match self.heuristics.get(&task.goal_state) {
+            Some((heuristic, _)) => heuristic.clone(),
+            None => Arc::new(ReverseResumableAStar::new(
+                self.transition_system.clone(),
+                task.clone(),
+                H::build(self.transition_system.clone(), Arc::new(task.reverse())),
+            )),
+        }
+    }
+
+    /// Sets the maximum number of instances kept in the cache,
+    /// and evicts the least recently used ones if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of instances kept in the cache.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Returns the maximum number of instances kept in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of instances in the cache.
+    pub fn len(&self) -> usize {
+        self.heuristics.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heuristics.is_empty()
+    }
+
+    /// Evicts the least recently used instances until the capacity is respected.
+    fn evict(&mut self) {
+        while self.heuristics.len() > self.capacity {
+            let oldest = self
+                .heuristics
+                .iter()
+                .min_by_key(|(_, (_, last_use))| *last_use)
+                .map(|(goal, _)| goal.clone())
+                .unwrap();
+            self.heuristics.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, GraphEdgeId, GraphNodeId, Heuristic, HeuristicCache, SimpleHeuristic,
+        SimpleState, SimpleWorld, Task,
+    };
+
+    #[test]
+    fn test_cache() {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        let mut cache: HeuristicCache<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = HeuristicCache::new(transition_system, 2);
+
+        let task = |from: usize, to: usize| {
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(from)),
+                SimpleState(GraphNodeId(to)),
+                OrderedFloat(0.0),
+            ))
+        };
+
+        // The instance is shared by tasks with the same goal
+        let heuristic = cache.get(&task(0, 99));
+        assert_eq!(
+            heuristic.get_heuristic(&SimpleState(GraphNodeId(0))),
+            Some(OrderedFloat(18.0))
+        );
+        let shared = cache.get(&task(90, 99));
+        assert!(Arc::ptr_eq(&heuristic, &shared));
+        assert_eq!(
+            shared.get_heuristic(&SimpleState(GraphNodeId(90))),
+            Some(OrderedFloat(9.0))
+        );
+
+        // The least recently used instance is evicted
+        let evicted = cache.get(&task(0, 9));
+        cache.get(&task(0, 99));
+        cache.get(&task(0, 90));
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&heuristic, &cache.get(&task(0, 99))));
+        assert!(!Arc::ptr_eq(&evicted, &cache.get(&task(0, 9))));
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -3,17 +3,29 @@ use std::{
     hash::Hash,
     ops::{Add, Div, Sub},
     sync::Arc,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "serde")]
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter},
 };
 
 use fxhash::FxHashMap;
+use tuple::T2;
 
 use crate::{
-    CbsConfig, ConflictBasedSearch, Heuristic, HeuristicBuilder, Interval, LimitValues,
-    ReverseResumableAStar, SippState, Solution, State, Task, TransitionSystem,
+    search::{find_conflict, Constraint},
+    Action, CbsConfig, ConflictBasedSearch, Heuristic, HeuristicBuilder, HeuristicCache, Interval,
+    LimitValues, Move, ReverseResumableAStar, SippState, Solution, State, Task, TransitionSystem,
 };
 
+/// The default number of heuristics to goal states kept between planning requests.
+const DEFAULT_CACHE_CAPACITY: usize = 256;
+
 /// A lifelong planner that supports requests for new tasks while other tasks are being executed.
-/// It uses Conflict-Based Search under the hood.
+/// It uses Conflict-Based Search under the hood. Agents are identified by the order in which
+/// they are added, and keep their identifier when other agents are removed.
 pub struct Lifelong<TS, S, A, C, DC, H>
 where
     TS: TransitionSystem<S, A, C, DC> + Send + Sync,
@@ -44,11 +56,17 @@ where
         + Sync,
     H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC> + Send + Sync,
 {
-    transition_system: Arc<TS>,
     solver: ConflictBasedSearch<TS, S, A, C, DC, H>,
     tasks: Vec<Arc<Task<S, C>>>,
     solutions: Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>,
     heuristic_to_pivots: Vec<Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>,
+    heuristics: HeuristicCache<TS, S, A, C, DC, H>,
+    transition_system: Arc<TS>,
+    obstacles: Vec<Vec<Move<S, A, C, DC>>>,
+    closures: Vec<Arc<Constraint<S, C, DC>>>,
+    active: Vec<bool>,
+    disabled: Vec<bool>,
+    window: Option<DC>,
     collision_precision: DC,
 }
 
@@ -97,85 +115,877 @@ where
         collision_precision: DC,
     ) -> Self {
         let solver = ConflictBasedSearch::new(transition_system.clone());
-        let mut tasks = vec![];
-        let mut solutions = vec![];
-        let mut heuristic_to_pivots = vec![];
+        let mut planner = Self {
+            heuristics: HeuristicCache::new(transition_system.clone(), DEFAULT_CACHE_CAPACITY),
+            transition_system,
+            solver,
+            tasks: vec![],
+            solutions: vec![],
+            heuristic_to_pivots: vec![],
+            obstacles: vec![],
+            closures: vec![],
+            active: vec![],
+            disabled: vec![],
+            window: None,
+            collision_precision,
+        };
         for initial_state in initial_states {
+            planner.add_agent(initial_state, initial_cost);
+        }
+        planner
+    }
+
+    /// Adds a new agent standing still at the given state from the given time,
+    /// and returns its identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The initial state of the agent.
+    /// * `time` - The time at which the agent appears.
+    pub fn add_agent(&mut self, state: S, time: C) -> usize {
+        let task = Arc::new(Task::new(state.clone(), state.clone(), time));
+        self.heuristic_to_pivots.push(self.heuristics.get(&task));
+        // The agent stands still at its initial position.
+        self.solutions.push(Solution {
+            cost: C::default(),
+            steps: vec![(
+                Arc::new(SippState {
+                    safe_interval: Interval::default(),
+                    internal_state: state,
+                }),
+                time,
+            )],
+            actions: vec![],
+        });
+        self.tasks.push(task);
+        self.active.push(true);
+        self.disabled.push(false);
+        self.solutions.len() - 1
+    }
+
+    /// Removes the given agent from subsequent planning requests. The remaining moves of its
+    /// current path become an obstacle, so the agent stops constraining the other agents
+    /// once it has completed its path. The identifiers of the other agents are unchanged.
+    /// Unknown or already removed agents are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent to remove.
+    pub fn remove_agent(&mut self, agent: usize) {
+        if self.active.get(agent) != Some(&true) {
+            return;
+        }
+        self.active[agent] = false;
+
+        let solution = &self.solutions[agent];
+        let moves = solution
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                Move::new(
+                    usize::MAX,
+                    solution.steps[i].0.internal_state.clone(),
+                    solution.steps[i + 1].0.internal_state.clone(),
+                    action.action,
+                    Interval::new(solution.steps[i].1, solution.steps[i + 1].1),
+                )
+            })
+            .collect::<Vec<_>>();
+        if !moves.is_empty() {
+            self.obstacles.push(moves);
+        }
+        self.closures.retain(|closure| closure.agent != agent);
+    }
+
+    /// Restores a lifelong planner from the given snapshot. The heuristics are rebuilt lazily,
+    /// the distances to the goals being only computed again when needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition_system` - The transition system in which the agents navigate.
+    /// * `snapshot` - The snapshot of the planner.
+    pub fn restore(transition_system: Arc<TS>, snapshot: LifelongSnapshot<S, A, C, DC>) -> Self {
+        let mut heuristics =
+            HeuristicCache::new(transition_system.clone(), snapshot.heuristic_cache_capacity);
+        Self {
+            solver: ConflictBasedSearch::new(transition_system.clone()),
+            heuristic_to_pivots: snapshot
+                .tasks
+                .iter()
+                .map(|task| heuristics.get(task))
+                .collect(),
+            heuristics,
+            transition_system,
+            tasks: snapshot.tasks,
+            solutions: snapshot.solutions,
+            obstacles: snapshot.obstacles,
+            closures: snapshot.closures.into_iter().map(Arc::new).collect(),
+            active: snapshot.active,
+            disabled: snapshot.disabled,
+            window: snapshot.window,
+            collision_precision: snapshot.collision_precision,
+        }
+    }
+
+    /// Returns a snapshot of the planner at the given time, from which it can be restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The current time, stored in the snapshot.
+    pub fn snapshot(&self, time: C) -> LifelongSnapshot<S, A, C, DC> {
+        LifelongSnapshot {
+            time,
+            tasks: self.tasks.clone(),
+            solutions: self.solutions.clone(),
+            obstacles: self.obstacles.clone(),
+            closures: self.closures.iter().map(|c| c.as_ref().clone()).collect(),
+            active: self.active.clone(),
+            disabled: self.disabled.clone(),
+            window: self.window,
+            collision_precision: self.collision_precision,
+            heuristic_cache_capacity: self.heuristics.capacity(),
+        }
+    }
+
+    /// Writes a snapshot of the planner at the given time to the given file, in JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - The file to write.
+    /// * `time` - The current time, stored in the snapshot.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, filename: &str, time: C) -> io::Result<()>
+    where
+        LifelongSnapshot<S, A, C, DC>: serde::Serialize,
+    {
+        let file = BufWriter::new(File::create(filename)?);
+        serde_json::to_writer(file, &self.snapshot(time))?;
+        Ok(())
+    }
+
+    /// Restores a lifelong planner from the snapshot in the given file, written by [`Lifelong::save`],
+    /// and returns it with the time of the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition_system` - The transition system in which the agents navigate.
+    /// * `filename` - The file to read.
+    #[cfg(feature = "serde")]
+    pub fn load(transition_system: Arc<TS>, filename: &str) -> io::Result<(Self, C)>
+    where
+        LifelongSnapshot<S, A, C, DC>: serde::de::DeserializeOwned,
+    {
+        let file = BufReader::new(File::open(filename)?);
+        let snapshot: LifelongSnapshot<S, A, C, DC> = serde_json::from_reader(file)?;
+        let time = snapshot.time;
+        Ok((Self::restore(transition_system, snapshot), time))
+    }
+
+    /// Sets the maximum number of heuristics to goal states kept between planning requests,
+    /// which are shared by all agents with the same goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of heuristics to keep.
+    pub fn set_heuristic_cache_capacity(&mut self, capacity: usize) {
+        self.heuristics.set_capacity(capacity);
+    }
+
+    /// Sets the window in which conflicts are resolved by subsequent planning requests,
+    /// starting from the earliest start of the new tasks, or None to resolve all conflicts.
+    /// Beyond the window, agents follow their individually optimal paths,
+    /// and [`Lifelong::replan`] should be called before they reach the end of the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The duration of the window.
+    pub fn set_window(&mut self, window: Option<DC>) {
+        self.window = window;
+    }
+
+    /// Marks the given agent as broken down at the given time. The agent stays forever where it is
+    /// at that time, and cannot be given new tasks. The path of an agent that is moving between
+    /// two states stops before that move, and the agent becomes a permanent obstacle on the edge:
+    /// both states are blocked and the actions between them are closed from the given time.
+    /// The other agents whose current path collides with the disabled agent are then replanned
+    /// to their current goals, starting from their positions at the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent that breaks down.
+    /// * `time` - The time at which the agent breaks down.
+    /// * `n_threads` - The number of threads to use.
+    /// * `time_limit` - The time limit for the search.
+    pub fn mark_disabled(
+        &mut self,
+        agent: usize,
+        time: C,
+        n_threads: usize,
+        time_limit: Option<Duration>,
+    ) -> LifelongOutcome<'_, S, A, C, DC> {
+        let mut blocking = vec![];
+        if self.is_active(agent) && !self.disabled[agent] {
+            self.disabled[agent] = true;
+
+            let (mut prefix, ongoing_move) = self.get_executed_prefix(agent, time);
+            if let Some(ongoing_move) = &ongoing_move {
+                // The agent cannot complete its move
+                prefix.steps.pop();
+                prefix.actions.pop();
+                prefix.cost = ongoing_move.interval.start;
+            }
+            let (state, end) = prefix.steps.last().unwrap();
             let task = Arc::new(Task::new(
-                initial_state.clone(),
-                initial_state.clone(),
-                initial_cost,
+                state.internal_state.clone(),
+                state.internal_state.clone(),
+                *end,
             ));
-            heuristic_to_pivots.push(Arc::new(ReverseResumableAStar::new(
-                transition_system.clone(),
-                task.clone(),
-                H::build(transition_system.clone(), Arc::new(task.reverse())),
+            self.heuristic_to_pivots[agent] = self.heuristics.get(&task);
+            self.tasks[agent] = task;
+            self.solutions[agent] = prefix;
+            blocking.push(self.solutions[agent].clone());
+
+            if let Some(ongoing_move) = ongoing_move {
+                // The agent is stuck on the edge, which blocks both of its states
+                let interval = Interval::new(time, C::max_value());
+                for state in [&ongoing_move.from, &ongoing_move.to] {
+                    blocking.push(Solution {
+                        cost: time,
+                        steps: vec![(
+                            Arc::new(SippState {
+                                safe_interval: Interval::default(),
+                                internal_state: state.clone(),
+                            }),
+                            time,
+                        )],
+                        actions: vec![],
+                    });
+                    // The obstacle is tagged with the agent, whose own path ignores it
+                    self.obstacles.push(vec![Move::new(
+                        agent,
+                        state.clone(),
+                        state.clone(),
+                        None,
+                        interval,
+                    )]);
+                }
+                self.add_action_closure(
+                    ongoing_move.from.clone(),
+                    ongoing_move.to.clone(),
+                    interval,
+                    None,
+                );
+                self.add_action_closure(ongoing_move.to, ongoing_move.from, interval, None);
+            }
+        }
+
+        // Replan the agents that collide with the disabled agent
+        let goals = (0..self.tasks.len())
+            .filter(|other| self.active[*other] && !self.disabled[*other])
+            .filter(|other| {
+                blocking.iter().any(|solution| {
+                    find_conflict(
+                        self.transition_system.as_ref(),
+                        T2((agent, solution), (*other, &self.solutions[*other])),
+                    )
+                    .is_some()
+                })
+            })
+            .map(|other| (other, self.tasks[other].goal_state.clone()))
+            .collect();
+        self.solve_request(&LifelongRequest {
+            goals,
+            time,
+            n_threads,
+            time_limit,
+        })
+    }
+
+    /// Returns the current paths of all agents, indexed by their identifier.
+    pub fn get_solutions(&self) -> &Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>> {
+        &self.solutions
+    }
+
+    /// Returns the length of a shortest path between the given states, ignoring the other agents,
+    /// or None if the goal cannot be reached. The distances are computed with the shared heuristics.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The initial state.
+    /// * `to` - The goal state.
+    pub fn get_distance(&mut self, from: &S, to: &S) -> Option<DC> {
+        let task = Arc::new(Task::new(from.clone(), to.clone(), C::default()));
+        self.heuristics.get(&task).get_heuristic(from)
+    }
+
+    /// Returns true if the given agent has been marked as broken down.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent to check.
+    pub fn is_disabled(&self, agent: usize) -> bool {
+        self.disabled.get(agent).copied().unwrap_or(false)
+    }
+
+    /// Returns true if the given agent has not been removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent to check.
+    pub fn is_active(&self, agent: usize) -> bool {
+        self.active.get(agent).copied().unwrap_or(false)
+    }
+
+    /// Adds a moving obstacle that all agents must avoid in subsequent planning requests,
+    /// given by its timed trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `trajectory` - The trajectory followed by the obstacle.
+    pub fn add_obstacle(&mut self, trajectory: &Solution<S, A, C, DC>) {
+        self.obstacles.push(trajectory.get_moves(usize::MAX));
+    }
+
+    /// Adds a moving obstacle that all agents must avoid in subsequent planning requests,
+    /// given by its timed moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - The moves performed by the obstacle.
+    pub fn add_obstacle_moves(&mut self, moves: Vec<Move<S, A, C, DC>>) {
+        self.obstacles.push(moves);
+    }
+
+    /// Closes the given state during the given interval in subsequent planning requests,
+    /// for all agents or for the given ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to close.
+    /// * `interval` - The interval during which no agent can be at the state.
+    /// * `agents` - The agents concerned by the closure, or None for all agents.
+    pub fn add_state_closure(
+        &mut self,
+        state: S,
+        interval: Interval<C, DC>,
+        agents: Option<&[usize]>,
+    ) {
+        for agent in agents.unwrap_or(&[usize::MAX]) {
+            self.closures
+                .push(Arc::new(Constraint::new_state_constraint(
+                    *agent,
+                    state.clone(),
+                    interval,
+                )));
+        }
+    }
+
+    /// Closes the action connecting the two given states during the given interval
+    /// in subsequent planning requests, for all agents or for the given ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The state the action goes from.
+    /// * `to` - The state the action goes to.
+    /// * `interval` - The interval during which no agent can perform the action.
+    /// * `agents` - The agents concerned by the closure, or None for all agents.
+    pub fn add_action_closure(
+        &mut self,
+        from: S,
+        to: S,
+        interval: Interval<C, DC>,
+        agents: Option<&[usize]>,
+    ) {
+        for agent in agents.unwrap_or(&[usize::MAX]) {
+            self.closures.push(Arc::new(Constraint::new_closure(
+                *agent,
+                from.clone(),
+                to.clone(),
+                interval,
             )));
-            // Each agent stands still at its initial position.
-            solutions.push(Solution {
-                cost: C::default(),
-                steps: vec![(
-                    Arc::new(SippState {
-                        safe_interval: Interval::default(),
-                        internal_state: initial_state,
-                    }),
-                    initial_cost,
-                )],
-                actions: vec![],
-            });
-            tasks.push(task);
         }
+    }
 
-        Self {
-            transition_system,
-            solver,
-            tasks,
-            solutions,
-            heuristic_to_pivots,
-            collision_precision,
+    /// Discards the parts of the current paths, obstacles and closures that are executed
+    /// before the given time. Each path then starts at the given time, or at the beginning
+    /// of the move performed at that time. Agents that have completed their path are given
+    /// a task to stay at their last state, which releases the heuristic of their previous task.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The current time.
+    pub fn advance_to(&mut self, time: C) {
+        for agent in 0..self.solutions.len() {
+            if !self.active[agent] {
+                continue;
+            }
+            let solution = &mut self.solutions[agent];
+
+            // Index of the last step that has started at the given time
+            let Some(current) = solution.steps.iter().rposition(|(_, t)| *t <= time) else {
+                continue;
+            };
+
+            if current + 1 == solution.steps.len() {
+                // The agent has completed its path
+                let state = solution.steps[current].0.clone();
+                solution.steps = vec![(state.clone(), time)];
+                solution.actions.clear();
+
+                let task = &self.tasks[agent];
+                if task.initial_state != task.goal_state {
+                    let task = Arc::new(Task::new(
+                        state.internal_state.clone(),
+                        state.internal_state.clone(),
+                        time,
+                    ));
+                    self.heuristic_to_pivots[agent] = self.heuristics.get(&task);
+                    self.tasks[agent] = task;
+                }
+                continue;
+            }
+
+            if solution.actions[current].action.is_none() {
+                // Shorten the current wait
+                solution.actions[current].cost = solution.steps[current + 1].1 - time;
+                solution.steps[current].1 = time;
+            }
+            solution.steps.drain(..current);
+            solution.actions.drain(..current);
         }
+
+        for obstacle in self.obstacles.iter_mut() {
+            obstacle.retain(|m| m.interval.end > time);
+        }
+        self.obstacles.retain(|obstacle| !obstacle.is_empty());
+        self.closures.retain(|closure| closure.interval.end > time);
     }
 
     /// Plan optimal paths to complete the given tasks,
     /// while avoiding other currently executing tasks.
+    /// If the tasks cannot be solved together within the time limit, they are solved one by one,
+    /// and the agents whose task cannot be solved keep their current path.
     ///
     /// # Arguments
     ///
     /// * `config` - The configuration of the planning request, specifying the new tasks to solve.
-    pub fn solve(
+    pub fn solve(&mut self, config: &LifelongConfig<S, C>) -> LifelongOutcome<'_, S, A, C, DC> {
+        let (solutions, rejected) =
+            self.solve_tasks(&config.tasks, config.n_threads, config.time_limit, vec![]);
+        self.solutions = solutions;
+        LifelongOutcome {
+            solutions: &self.solutions,
+            rejected,
+        }
+    }
+
+    /// Plan optimal paths to the given goals, starting from the positions of the agents
+    /// in their current paths at the given time. Agents that are moving between two states
+    /// at that time first complete their move, and the new paths are appended to the part
+    /// of the current paths executed until then. Agents whose goal cannot be reached keep
+    /// their current path, as in [`Lifelong::solve`].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The planning request, specifying the new goals and the current time.
+    pub fn solve_request(
         &mut self,
-        config: &LifelongConfig<S, C>,
-    ) -> Option<&Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>> {
-        for (agent, task) in &config.tasks {
-            self.tasks[*agent] = task.clone();
-            self.heuristic_to_pivots[*agent] = Arc::new(ReverseResumableAStar::new(
-                self.transition_system.clone(),
-                task.clone(),
-                H::build(self.transition_system.clone(), Arc::new(task.reverse())),
-            ));
+        request: &LifelongRequest<S, C>,
+    ) -> LifelongOutcome<'_, S, A, C, DC> {
+        let mut tasks = FxHashMap::default();
+        let mut prefixes = FxHashMap::default();
+        let mut ongoing_moves = vec![];
+        let mut rejected = vec![];
+        for (agent, goal) in &request.goals {
+            if !self.is_active(*agent) || self.disabled[*agent] {
+                rejected.push(*agent);
+                continue;
+            }
+            let (prefix, ongoing_move) = self.get_executed_prefix(*agent, request.time);
+            let (state, time) = prefix.steps.last().unwrap();
+            tasks.insert(
+                *agent,
+                Arc::new(Task::new(state.internal_state.clone(), goal.clone(), *time)),
+            );
+            prefixes.insert(*agent, prefix);
+            ongoing_moves.extend(ongoing_move.map(|m| vec![m]));
+        }
+
+        let (mut solutions, mut failed) =
+            self.solve_tasks(&tasks, request.n_threads, request.time_limit, ongoing_moves);
+
+        // Append the new paths to the executed parts of the current paths
+        for (agent, mut prefix) in prefixes {
+            if failed.contains(&agent) {
+                continue;
+            }
+            Self::append_path(&mut prefix, &solutions[agent]);
+            solutions[agent] = prefix;
+        }
+
+        rejected.append(&mut failed);
+        rejected.sort_unstable();
+        self.solutions = solutions;
+        LifelongOutcome {
+            solutions: &self.solutions,
+            rejected,
+        }
+    }
+
+    /// Plan new paths for all agents to their current goals, starting from their positions
+    /// in their current paths at the given time. In windowed mode, this resolves the conflicts
+    /// of the next window and should be called periodically.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time at which the new paths start.
+    /// * `n_threads` - The number of threads to use.
+    /// * `time_limit` - The time limit for the search.
+    pub fn replan(
+        &mut self,
+        time: C,
+        n_threads: usize,
+        time_limit: Option<Duration>,
+    ) -> LifelongOutcome<'_, S, A, C, DC> {
+        let request = LifelongRequest {
+            goals: (0..self.tasks.len())
+                .filter(|agent| self.active[*agent] && !self.disabled[*agent])
+                .map(|agent| (agent, self.tasks[agent].goal_state.clone()))
+                .collect(),
+            time,
+            n_threads,
+            time_limit,
+        };
+        self.solve_request(&request)
+    }
+
+    /// Plan an optimal path for the given agent to the given goal, as [`Lifelong::solve_request`] would,
+    /// and returns it without committing it. The tasks, paths and heuristics of the agents are unchanged.
+    /// Returns None if the agent was removed or disabled, or if no path is found within the time limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent to plan for.
+    /// * `goal` - The goal of the agent.
+    /// * `time` - The time at which the new path starts.
+    /// * `time_limit` - The time limit for the search.
+    pub fn evaluate(
+        &self,
+        agent: usize,
+        goal: S,
+        time: C,
+        time_limit: Option<Duration>,
+    ) -> Option<Solution<Arc<SippState<S, C, DC>>, A, C, DC>> {
+        if !self.is_active(agent) || self.disabled[agent] {
+            return None;
+        }
+
+        let (mut prefix, ongoing_move) = self.get_executed_prefix(agent, time);
+        let (state, start) = prefix.steps.last().unwrap();
+        let task = Arc::new(Task::new(state.internal_state.clone(), goal, *start));
+        let horizon = self.window.map(|window| task.initial_cost + window);
+        let additional_obstacles = ongoing_move
+            .map(|m| vec![m])
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let mut tasks = FxHashMap::default();
+        let mut heuristics = FxHashMap::default();
+        heuristics.insert(agent, self.heuristics.peek(&task));
+        tasks.insert(agent, task);
+
+        let (agents, cbs_config) = self.get_cbs_config(
+            &[agent],
+            &tasks,
+            &heuristics,
+            &self.solutions,
+            1,
+            time_limit,
+            horizon,
+            &additional_obstacles,
+        );
+        let mut solver = ConflictBasedSearch::new(self.transition_system.clone());
+        let solutions = solver.solve(&cbs_config)?;
+
+        let index = agents.iter().position(|a| *a == agent).unwrap();
+        Self::append_path(&mut prefix, &solutions[index]);
+        Some(prefix)
+    }
+
+    /// Appends the given path to the given executed part of a path, which ends where the given path starts.
+    fn append_path(
+        prefix: &mut Solution<Arc<SippState<S, C, DC>>, A, C, DC>,
+        solution: &Solution<Arc<SippState<S, C, DC>>, A, C, DC>,
+    ) {
+        prefix.steps.pop();
+        prefix.steps.extend(solution.steps.iter().cloned());
+        prefix.actions.extend(solution.actions.iter().copied());
+        prefix.cost = solution.cost;
+    }
+
+    /// Returns the part of the current path of the given agent that is executed at the given time,
+    /// which ends at the state and time from which the agent can follow a new path,
+    /// and the move that the agent is performing at that time, if any.
+    fn get_executed_prefix(
+        &self,
+        agent: usize,
+        time: C,
+    ) -> (
+        Solution<Arc<SippState<S, C, DC>>, A, C, DC>,
+        Option<Move<S, A, C, DC>>,
+    ) {
+        let solution = &self.solutions[agent];
+        let mut prefix = Solution::default();
+
+        for (i, action) in solution.actions.iter().enumerate() {
+            let (state, start) = &solution.steps[i];
+            let (next, end) = &solution.steps[i + 1];
+            prefix.steps.push(solution.steps[i].clone());
+
+            if *end <= time {
+                prefix.actions.push(*action);
+                continue;
+            }
+
+            if let (Some(a), true) = (action.action, *start < time) {
+                // The agent completes its current move
+                prefix.actions.push(*action);
+                prefix.steps.push(solution.steps[i + 1].clone());
+                prefix.cost = *end;
+                let ongoing_move = Move::new(
+                    agent,
+                    state.internal_state.clone(),
+                    next.internal_state.clone(),
+                    Some(a),
+                    Interval::new(*start, *end),
+                );
+                return (prefix, Some(ongoing_move));
+            }
+
+            // The agent stops waiting, or has not started yet
+            if *start < time {
+                prefix.actions.push(Action::wait(time - *start));
+                prefix.steps.push((state.clone(), time));
+            }
+            prefix.cost = time.max(*start);
+            return (prefix, None);
+        }
+
+        // The agent has completed its path, and waits at its last state
+        let (state, end) = solution.steps.last().unwrap();
+        prefix.steps.push((state.clone(), *end));
+        if *end < time {
+            prefix.actions.push(Action::wait(time - *end));
+            prefix.steps.push((state.clone(), time));
+        }
+        prefix.cost = time.max(*end);
+        (prefix, None)
+    }
+
+    /// Plan optimal paths to complete the given tasks, while avoiding the current paths
+    /// of the other agents and the given additional obstacles, and returns all paths
+    /// and the agents whose task was rejected, which keep their current path.
+    /// The tasks are first solved together, then one by one if this fails, all attempts sharing
+    /// the time limit. The agents that could not be attempted before it expired are rejected.
+    fn solve_tasks(
+        &mut self,
+        tasks: &FxHashMap<usize, Arc<Task<S, C>>>,
+        n_threads: usize,
+        time_limit: Option<Duration>,
+        additional_obstacles: Vec<Vec<Move<S, A, C, DC>>>,
+    ) -> (
+        Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>,
+        Vec<usize>,
+    ) {
+        let start = Instant::now();
+        // The time left for the next attempt, or None if the time limit is reached
+        let remaining = || match time_limit {
+            Some(time_limit) => time_limit.checked_sub(start.elapsed()).map(Some),
+            None => Some(None),
+        };
+
+        let mut rejected = vec![];
+        let mut heuristics = FxHashMap::default();
+        for (agent, task) in tasks {
+            if !self.active[*agent] || self.disabled[*agent] {
+                rejected.push(*agent);
+                continue;
+            }
+            heuristics.insert(*agent, self.heuristics.get(task));
+        }
+        let horizon = self.window.and_then(|window| {
+            tasks
+                .values()
+                .map(|task| task.initial_cost)
+                .min()
+                .map(|start| start + window)
+        });
+
+        let mut agents = heuristics.keys().copied().collect::<Vec<_>>();
+        agents.sort_unstable();
+
+        let mut solutions = self.solutions.clone();
+        let joint = remaining().and_then(|time_limit| {
+            self.solve_agents(
+                &agents,
+                tasks,
+                &heuristics,
+                &solutions,
+                n_threads,
+                time_limit,
+                horizon,
+                &additional_obstacles,
+            )
+        });
+        let accepted = match joint {
+            Some(new_solutions) => {
+                solutions = new_solutions;
+                agents
+            }
+            None => {
+                // Solve the tasks one by one, the other agents following their current path
+                let mut accepted = vec![];
+                for agent in agents {
+                    let Some(time_limit) = remaining() else {
+                        rejected.push(agent);
+                        continue;
+                    };
+                    match self.solve_agents(
+                        &[agent],
+                        tasks,
+                        &heuristics,
+                        &solutions,
+                        n_threads,
+                        time_limit,
+                        horizon,
+                        &additional_obstacles,
+                    ) {
+                        Some(mut new_solutions) => {
+                            solutions[agent] = new_solutions.swap_remove(agent);
+                            accepted.push(agent);
+                        }
+                        None => rejected.push(agent),
+                    }
+                }
+                accepted
+            }
+        };
+
+        for agent in accepted {
+            self.tasks[agent] = tasks[&agent].clone();
+            self.heuristic_to_pivots[agent] = heuristics[&agent].clone();
+        }
+
+        rejected.sort_unstable();
+        (solutions, rejected)
+    }
+
+    /// Plan optimal paths for the given agents to complete their new task, while avoiding the given paths
+    /// of the other agents, the obstacles and the given additional obstacles.
+    #[allow(clippy::too_many_arguments)]
+    fn solve_agents(
+        &mut self,
+        planned: &[usize],
+        tasks: &FxHashMap<usize, Arc<Task<S, C>>>,
+        heuristics: &FxHashMap<usize, Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>,
+        solutions: &[Solution<Arc<SippState<S, C, DC>>, A, C, DC>],
+        n_threads: usize,
+        time_limit: Option<Duration>,
+        horizon: Option<C>,
+        additional_obstacles: &[Vec<Move<S, A, C, DC>>],
+    ) -> Option<Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>> {
+        let (agents, cbs_config) = self.get_cbs_config(
+            planned,
+            tasks,
+            heuristics,
+            solutions,
+            n_threads,
+            time_limit,
+            horizon,
+            additional_obstacles,
+        );
+
+        let mut new_solutions = solutions.to_vec();
+        for (agent, solution) in agents.iter().zip(self.solver.solve(&cbs_config)?) {
+            new_solutions[*agent] = solution;
+        }
+        Some(new_solutions)
+    }
+
+    /// Returns the active agents, in the order in which they are given to the solver,
+    /// and the configuration of the solver to plan for the given agents.
+    #[allow(clippy::too_many_arguments)]
+    fn get_cbs_config(
+        &self,
+        planned: &[usize],
+        tasks: &FxHashMap<usize, Arc<Task<S, C>>>,
+        heuristics: &FxHashMap<usize, Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>,
+        solutions: &[Solution<Arc<SippState<S, C, DC>>, A, C, DC>],
+        n_threads: usize,
+        time_limit: Option<Duration>,
+        horizon: Option<C>,
+        additional_obstacles: &[Vec<Move<S, A, C, DC>>],
+    ) -> (Vec<usize>, CbsConfig<TS, S, A, C, DC, H>) {
+        // Only active agents are given to the solver, which identifies them by their rank
+        let agents = (0..self.tasks.len())
+            .filter(|agent| self.active[*agent])
+            .collect::<Vec<_>>();
+        let mut indices = vec![usize::MAX; self.tasks.len()];
+        for (index, agent) in agents.iter().enumerate() {
+            indices[*agent] = index;
         }
+        let get_index = |agent: usize| indices.get(agent).copied().unwrap_or(usize::MAX);
+
+        let (agent_tasks, agent_heuristics): (Vec<_>, Vec<_>) = agents
+            .iter()
+            .map(|agent| {
+                if planned.contains(agent) {
+                    (tasks[agent].clone(), heuristics[agent].clone())
+                } else {
+                    (
+                        self.tasks[*agent].clone(),
+                        self.heuristic_to_pivots[*agent].clone(),
+                    )
+                }
+            })
+            .unzip();
 
         let mut cbs_config = CbsConfig::new_with_pivots(
-            self.tasks.clone(),
-            Arc::new(self.tasks.iter().map(|t| t.goal_state.clone()).collect()),
-            Arc::new(self.heuristic_to_pivots.clone()),
+            agent_tasks.clone(),
+            Arc::new(agent_tasks.iter().map(|t| t.goal_state.clone()).collect()),
+            Arc::new(agent_heuristics),
             self.collision_precision,
-            config.n_threads,
-            None,
+            n_threads,
+            time_limit,
         );
+        cbs_config.horizon = horizon;
 
-        for agent in 0..self.tasks.len() {
-            if !config.tasks.contains_key(&agent) {
-                cbs_config.add_frozen(agent, self.solutions[agent].clone());
+        for (index, agent) in agents.iter().enumerate() {
+            if !planned.contains(agent) {
+                cbs_config.add_frozen(index, solutions[*agent].clone());
             }
         }
 
-        if let Some(solutions) = self.solver.solve(&cbs_config) {
-            self.solutions = solutions;
-            Some(&self.solutions)
-        } else {
-            None
+        for obstacle in self.obstacles.iter().chain(additional_obstacles) {
+            let mut obstacle = obstacle.clone();
+            for m in obstacle.iter_mut() {
+                m.agent = get_index(m.agent);
+            }
+            cbs_config.add_obstacle_moves(obstacle);
+        }
+
+        for closure in &self.closures {
+            if closure.agent == usize::MAX {
+                cbs_config.add_external_constraint(closure.clone());
+            } else if get_index(closure.agent) != usize::MAX {
+                let mut closure = closure.as_ref().clone();
+                closure.agent = get_index(closure.agent);
+                cbs_config.add_external_constraint(Arc::new(closure));
+            }
         }
+
+        (agents, cbs_config)
     }
 }
 
@@ -186,21 +996,86 @@ where
     C: Copy,
 {
     /// The tasks to solve.
-    pub tasks: FxHashMap<usize, Arc<Task<S, C>>>, // TODO: only require new destination and use current position as initial state?
+    pub tasks: FxHashMap<usize, Arc<Task<S, C>>>,
+    /// The number of threads to use.
+    pub n_threads: usize,
+    /// The time limit for the search.
+    pub time_limit: Option<Duration>,
+}
+
+/// A planning request that only specifies new goals, the agents starting from their
+/// positions in their current paths at the given time.
+pub struct LifelongRequest<S, C>
+where
+    S: State + Eq + Clone,
+    C: Copy,
+{
+    /// The new goal of each agent to plan for.
+    pub goals: FxHashMap<usize, S>,
+    /// The time at which the new paths start.
+    pub time: C,
     /// The number of threads to use.
     pub n_threads: usize,
+    /// The time limit for the search.
+    pub time_limit: Option<Duration>,
+}
+
+/// A snapshot of the tasks, paths, obstacles and agents of a lifelong planner at a given time.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LifelongSnapshot<S, A, C, DC>
+where
+    S: Debug + State + Eq + Clone,
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy + Default,
+    DC: Ord,
+{
+    time: C,
+    tasks: Vec<Arc<Task<S, C>>>,
+    solutions: Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>,
+    obstacles: Vec<Vec<Move<S, A, C, DC>>>,
+    closures: Vec<Constraint<S, C, DC>>,
+    active: Vec<bool>,
+    disabled: Vec<bool>,
+    window: Option<DC>,
+    collision_precision: DC,
+    heuristic_cache_capacity: usize,
+}
+
+impl<S, A, C, DC> LifelongSnapshot<S, A, C, DC>
+where
+    S: Debug + State + Eq + Clone,
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy + Default,
+    DC: Ord,
+{
+    /// Returns the time at which the snapshot was taken.
+    pub fn time(&self) -> C {
+        self.time
+    }
+}
+
+/// The outcome of a planning request.
+pub struct LifelongOutcome<'a, S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy + Default,
+{
+    /// The current paths of all agents.
+    pub solutions: &'a Vec<Solution<Arc<SippState<S, C, DC>>, A, C, DC>>,
+    /// The agents whose task was rejected, because it could not be solved within the time limit
+    /// or because the agent was removed or disabled. Those agents keep their previous path.
+    pub rejected: Vec<usize>,
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{sync::Arc, time::Duration};
 
     use fxhash::FxHashMap;
     use ordered_float::OrderedFloat;
 
     use crate::{
-        simple_graph, GraphEdgeId, GraphNodeId, Lifelong, LifelongConfig, SimpleHeuristic,
-        SimpleState, SimpleWorld, Task,
+        simple_graph, GraphEdgeId, GraphNodeId, Interval, Lifelong, LifelongConfig,
+        LifelongRequest, LimitValues, Move, SimpleHeuristic, SimpleState, SimpleWorld, Task,
     };
 
     #[test]
@@ -232,6 +1107,7 @@ mod tests {
         let mut config = LifelongConfig {
             tasks: FxHashMap::default(),
             n_threads: 1,
+            time_limit: None,
         };
 
         config.tasks.insert(
@@ -251,9 +1127,643 @@ mod tests {
             )),
         );
 
-        let solutions = planner.solve(&config).unwrap();
+        let solutions = planner.solve(&config).solutions;
 
         assert_eq!(solutions[0].cost + solutions[2].cost, OrderedFloat(17.0));
         assert!(solutions[1].actions.is_empty());
     }
+
+    #[test]
+    fn test_obstacles() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        planner.add_obstacle_moves(vec![Move::new(
+            0,
+            SimpleState(GraphNodeId(5)),
+            SimpleState(GraphNodeId(5)),
+            None,
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+        )]);
+
+        let mut config = LifelongConfig {
+            tasks: FxHashMap::default(),
+            n_threads: 1,
+            time_limit: None,
+        };
+
+        config.tasks.insert(
+            0,
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(9)),
+                OrderedFloat(0.0),
+            )),
+        );
+
+        let solutions = planner.solve(&config).solutions;
+
+        assert_eq!(solutions[0].cost, OrderedFloat(11.0));
+    }
+
+    #[test]
+    fn test_request() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(55))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        // Agent 0 is moving from (0, 2) to (0, 3), and agent 1 is still at its initial position
+        request.time = OrderedFloat(2.5);
+        request.goals.insert(0, SimpleState(GraphNodeId(93)));
+        request.goals.insert(1, SimpleState(GraphNodeId(59)));
+        let solutions = planner.solve_request(&request).solutions;
+
+        assert_eq!(solutions[0].cost, OrderedFloat(12.0));
+        assert_eq!(
+            solutions[0].steps[0].0.internal_state,
+            SimpleState(GraphNodeId(0))
+        );
+        assert_eq!(
+            solutions[0].steps[3].0.internal_state,
+            SimpleState(GraphNodeId(3))
+        );
+        assert_eq!(solutions[0].steps[3].1, OrderedFloat(3.0));
+        assert_eq!(solutions[0].steps.len(), solutions[0].actions.len() + 1);
+
+        assert_eq!(solutions[1].cost, OrderedFloat(6.5));
+        assert_eq!(solutions[1].steps[1].1, OrderedFloat(2.5));
+        assert_eq!(solutions[1].actions[0].action, None);
+    }
+
+    #[test]
+    fn test_advance() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(55))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+        planner.add_state_closure(
+            SimpleState(GraphNodeId(50)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(1.0)),
+            None,
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        // Agent 0 is moving from (0, 2) to (0, 3)
+        planner.advance_to(OrderedFloat(2.5));
+        assert_eq!(
+            planner.solutions[0].steps[0],
+            (planner.solutions[0].steps[0].0.clone(), OrderedFloat(2.0))
+        );
+        assert_eq!(
+            planner.solutions[0].steps[0].0.internal_state,
+            SimpleState(GraphNodeId(2))
+        );
+        assert_eq!(planner.solutions[0].steps.len(), 8);
+        assert_eq!(planner.solutions[1].steps.len(), 1);
+        assert_eq!(planner.solutions[1].steps[0].1, OrderedFloat(2.5));
+        assert!(planner.closures.is_empty());
+
+        // Agent 0 has completed its path
+        planner.advance_to(OrderedFloat(20.0));
+        assert_eq!(planner.solutions[0].steps.len(), 1);
+        assert_eq!(planner.tasks[0].initial_state, SimpleState(GraphNodeId(9)));
+        assert_eq!(planner.tasks[0].goal_state, SimpleState(GraphNodeId(9)));
+
+        request.time = OrderedFloat(20.0);
+        request.goals.insert(0, SimpleState(GraphNodeId(0)));
+        let solutions = planner.solve_request(&request).solutions;
+        assert_eq!(solutions[0].cost, OrderedFloat(29.0));
+    }
+
+    #[test]
+    fn test_agents() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(2))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(1, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        // Agent 1 leaves once it reaches its goal, so agent 0 can then go there
+        planner.remove_agent(1);
+        assert!(!planner.is_active(1));
+
+        // Unknown agents are ignored
+        planner.remove_agent(5);
+        planner.add_action_closure(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(1.0)),
+            Some(&[5]),
+        );
+
+        assert_eq!(
+            planner.add_agent(SimpleState(GraphNodeId(19)), OrderedFloat(0.0)),
+            2
+        );
+
+        request.goals.clear();
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        request.goals.insert(1, SimpleState(GraphNodeId(0)));
+        let outcome = planner.solve_request(&request);
+        assert_eq!(outcome.rejected, vec![1]);
+        let solutions = outcome.solutions;
+        assert_eq!(solutions.len(), 3);
+        assert_eq!(solutions[0].cost, OrderedFloat(9.0));
+        assert_eq!(solutions[1].cost, OrderedFloat(7.0));
+        assert!(solutions[2].actions.is_empty());
+    }
+
+    #[test]
+    fn test_window() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(9))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+        planner.set_window(Some(OrderedFloat(4.0)));
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        request.goals.insert(1, SimpleState(GraphNodeId(0)));
+
+        // The agents meet in the middle of the corridor, after the window
+        let solutions = planner.solve_request(&request).solutions;
+        assert_eq!(solutions[0].cost + solutions[1].cost, OrderedFloat(18.0));
+
+        // The conflict is resolved when the window reaches it
+        let solutions = planner.replan(OrderedFloat(2.0), 1, None).solutions;
+        assert!(solutions[0].cost + solutions[1].cost > OrderedFloat(19.0));
+        assert_eq!(
+            solutions[0].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(9))
+        );
+        assert_eq!(
+            solutions[1].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(0))
+        );
+    }
+
+    #[test]
+    fn test_fallback() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(9)),
+                SimpleState(GraphNodeId(20)),
+            ],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        // The goal of agent 0 is occupied by agent 1 forever
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: Some(Duration::from_secs(1)),
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        request.goals.insert(2, SimpleState(GraphNodeId(29)));
+
+        let outcome = planner.solve_request(&request);
+        assert_eq!(outcome.rejected, vec![0]);
+        assert!(outcome.solutions[0].actions.is_empty());
+        assert_eq!(outcome.solutions[2].cost, OrderedFloat(9.0));
+        assert_eq!(planner.tasks[0].goal_state, SimpleState(GraphNodeId(0)));
+        assert_eq!(planner.tasks[2].goal_state, SimpleState(GraphNodeId(29)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // Eight agents in a ring must rotate by half a turn, which exhausts the time limit
+        let ring = [0, 1, 2, 12, 22, 21, 20, 10];
+        let mut initial_states = ring
+            .iter()
+            .map(|n| SimpleState(GraphNodeId(*n)))
+            .collect::<Vec<_>>();
+        initial_states.push(SimpleState(GraphNodeId(99)));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            initial_states,
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: Some(Duration::from_millis(100)),
+        };
+        for (agent, _) in ring.iter().enumerate() {
+            request
+                .goals
+                .insert(agent, SimpleState(GraphNodeId(ring[(agent + 4) % 8])));
+        }
+        request.goals.insert(8, SimpleState(GraphNodeId(90)));
+
+        // The easy task of the last agent is not attempted alone once the time limit is reached
+        let outcome = planner.solve_request(&request);
+        assert_eq!(outcome.rejected, (0..9).collect::<Vec<_>>());
+        assert_eq!(planner.tasks[8].goal_state, SimpleState(GraphNodeId(99)));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(55))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+        let cached = planner.heuristics.len();
+
+        // Agent 0 is moving from (0, 2) to (0, 3) at time 2.5
+        let solution = planner
+            .evaluate(0, SimpleState(GraphNodeId(93)), OrderedFloat(2.5), None)
+            .unwrap();
+        assert_eq!(solution.cost, OrderedFloat(12.0));
+        let solution = planner
+            .evaluate(1, SimpleState(GraphNodeId(59)), OrderedFloat(2.5), None)
+            .unwrap();
+        assert_eq!(solution.cost, OrderedFloat(6.5));
+
+        // Nothing is committed
+        assert_eq!(planner.tasks[0].goal_state, SimpleState(GraphNodeId(9)));
+        assert_eq!(planner.solutions[0].cost, OrderedFloat(9.0));
+        assert!(planner.solutions[1].actions.is_empty());
+        assert_eq!(planner.heuristics.len(), cached);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![
+                SimpleState(GraphNodeId(5)),
+                SimpleState(GraphNodeId(79)),
+                SimpleState(GraphNodeId(39)),
+            ],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(95)));
+        request.goals.insert(1, SimpleState(GraphNodeId(70)));
+        request.goals.insert(2, SimpleState(GraphNodeId(30)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+        let unaffected = planner.solutions[1].steps.clone();
+        assert!(planner.solutions[2]
+            .steps
+            .iter()
+            .any(|(state, _)| state.internal_state == SimpleState(GraphNodeId(35))));
+
+        // Agent 0 breaks down while moving from (2, 5) to (3, 5), and does not complete its move
+        let outcome = planner.mark_disabled(0, OrderedFloat(2.5), 1, None);
+        assert!(outcome.rejected.is_empty());
+        let (state, end) = outcome.solutions[0].steps.last().unwrap();
+        assert_eq!(state.internal_state, SimpleState(GraphNodeId(25)));
+        assert_eq!(*end, OrderedFloat(2.0));
+        assert_eq!(outcome.solutions[0].cost, OrderedFloat(2.0));
+        assert_eq!(outcome.solutions[1].steps, unaffected);
+
+        // Agent 2 goes around both states
+        assert!(outcome.solutions[2].cost >= OrderedFloat(11.0));
+        assert!(outcome.solutions[2]
+            .steps
+            .iter()
+            .filter(|(_, time)| *time > OrderedFloat(2.5))
+            .all(
+                |(state, _)| state.internal_state != SimpleState(GraphNodeId(25))
+                    && state.internal_state != SimpleState(GraphNodeId(35))
+            ));
+
+        // The edge is closed in both directions
+        assert_eq!(planner.closures.len(), 2);
+        assert!(planner.closures.iter().all(|closure| {
+            closure.interval == Interval::new(OrderedFloat(2.5), LimitValues::max_value())
+        }));
+
+        assert!(planner.is_disabled(0));
+        request.goals.clear();
+        request.goals.insert(0, SimpleState(GraphNodeId(0)));
+        assert_eq!(planner.solve_request(&request).rejected, vec![0]);
+    }
+
+    #[test]
+    fn test_disabled_rejected() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system,
+            vec![
+                SimpleState(GraphNodeId(10)),
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(99)),
+            ],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(90)));
+        request.goals.insert(1, SimpleState(GraphNodeId(80)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        // Agent 1 follows agent 0, and cannot avoid it once it breaks down
+        let outcome = planner.mark_disabled(0, OrderedFloat(0.5), 1, None);
+        assert_eq!(outcome.rejected, vec![1]);
+
+        // The conflict between the two agents does not prevent planning for the others
+        request.time = OrderedFloat(1.0);
+        request.goals.clear();
+        request.goals.insert(2, SimpleState(GraphNodeId(91)));
+        let outcome = planner.solve_request(&request);
+        assert!(outcome.rejected.is_empty());
+        assert_eq!(outcome.solutions[2].cost, OrderedFloat(9.0));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system.clone(),
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(55))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+        planner.add_state_closure(
+            SimpleState(GraphNodeId(57)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(100.0)),
+            None,
+        );
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        let snapshot = planner.snapshot(OrderedFloat(2.5));
+        assert_eq!(snapshot.time(), OrderedFloat(2.5));
+        let mut restored: Lifelong<_, _, _, _, _, SimpleHeuristic> =
+            Lifelong::restore(transition_system, snapshot);
+        assert_eq!(
+            format!("{:?}", restored.solutions),
+            format!("{:?}", planner.solutions)
+        );
+
+        // Both planners plan the same paths, avoiding the closure
+        request.time = OrderedFloat(2.5);
+        request.goals.clear();
+        request.goals.insert(1, SimpleState(GraphNodeId(59)));
+        let expected = format!("{:?}", planner.solve_request(&request).solutions);
+        let outcome = restored.solve_request(&request);
+        assert_eq!(format!("{:?}", outcome.solutions), expected);
+        assert_eq!(outcome.solutions[1].cost, OrderedFloat(8.5));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save() {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+
+        let mut planner: Lifelong<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        > = Lifelong::new(
+            transition_system.clone(),
+            vec![SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(55))],
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+        planner.add_obstacle_moves(vec![Move::new(
+            0,
+            SimpleState(GraphNodeId(5)),
+            SimpleState(GraphNodeId(5)),
+            None,
+            Interval::new(OrderedFloat(0.0), OrderedFloat(20.0)),
+        )]);
+        planner.remove_agent(1);
+
+        let mut request = LifelongRequest {
+            goals: FxHashMap::default(),
+            time: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        request.goals.insert(0, SimpleState(GraphNodeId(9)));
+        assert!(planner.solve_request(&request).rejected.is_empty());
+
+        let file = std::env::temp_dir().join("caboose-lifelong-snapshot.json");
+        let filename = file.to_str().unwrap();
+        planner.save(filename, OrderedFloat(3.0)).unwrap();
+        let (restored, time) = Lifelong::<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            OrderedFloat<f64>,
+            OrderedFloat<f64>,
+            SimpleHeuristic,
+        >::load(transition_system, filename)
+        .unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(time, OrderedFloat(3.0));
+        assert!(!restored.is_active(1));
+        assert_eq!(restored.obstacles.len(), 1);
+        assert_eq!(
+            format!("{:?}", restored.solutions),
+            format!("{:?}", planner.solutions)
+        );
+    }
 }
@@ -0,0 +1,654 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Div, Sub},
+    time::Duration,
+};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    Heuristic, HeuristicBuilder, Lifelong, LifelongRequest, LimitValues, State, TransitionSystem,
+};
+
+/// The policy used to assign the pending tasks to the idle agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MapdPolicy {
+    /// The pending tasks are considered by order of release, and each one is assigned
+    /// to the nearest idle agent for which its pickup and delivery states are not
+    /// the endpoint of another agent.
+    Greedy,
+    /// The idle agents are considered by order of identifier, and each one takes the nearest
+    /// pending task whose pickup and delivery states are not the endpoint of another agent.
+    TokenPassing,
+}
+
+/// The current activity of an agent in a pickup and delivery problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MapdStatus<S> {
+    /// The agent has no task and no parking endpoint.
+    Idle,
+    /// The agent has no task, and moves to or waits at the given parking endpoint.
+    Parking(S),
+    /// The agent moves to the pickup state of the given task.
+    Pickup(usize),
+    /// The agent carries the given task to its delivery state.
+    Delivery(usize),
+    /// The agent has broken down, and stays forever where it is.
+    Disabled,
+}
+
+/// A task consisting in moving from a pickup state to a delivery state.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PickupDeliveryTask<S, C> {
+    /// The state at which the task is picked up.
+    pub pickup: S,
+    /// The state at which the task is delivered.
+    pub delivery: S,
+    /// The time at which the task is released.
+    pub release: C,
+}
+
+/// The service record of a pickup and delivery task.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceRecord<C> {
+    /// The agent to which the task is assigned, if any.
+    pub agent: Option<usize>,
+    /// The time at which the task is released.
+    pub release: C,
+    /// The time at which the task is picked up, if it has been.
+    pub pickup: Option<C>,
+    /// The time at which the task is delivered, if it has been.
+    pub delivery: Option<C>,
+    /// Whether the agent broke down while carrying the task, which is then never delivered.
+    pub failed: bool,
+}
+
+impl<C> ServiceRecord<C>
+where
+    C: Copy,
+{
+    /// Returns the time between the release and the delivery of the task, if it has been delivered.
+    pub fn service_time<DC>(&self) -> Option<DC>
+    where
+        C: Sub<C, Output = DC>,
+    {
+        self.delivery.map(|delivery| delivery - self.release)
+    }
+}
+
+/// A Multi-Agent Pickup and Delivery layer over the lifelong planner. Tasks are released online,
+/// assigned to the idle agents, which first plan a path to the pickup state of their task,
+/// then to its delivery state. Agents without task move to a free parking endpoint, if any.
+pub struct Mapd<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC> + Send + Sync,
+    S: Debug + State + Eq + Hash + Clone + Send + Sync,
+    A: Debug + Copy + Send + Sync,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues
+        + Send
+        + Sync,
+    DC: Debug
+        + Hash
+        + Ord
+        + Add<DC, Output = DC>
+        + Sub<DC, Output = DC>
+        + Div<f64, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
+    H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC> + Send + Sync,
+{
+    planner: Lifelong<TS, S, A, C, DC, H>,
+    policy: MapdPolicy,
+    parking: Vec<S>,
+    tasks: Vec<PickupDeliveryTask<S, C>>,
+    records: Vec<ServiceRecord<C>>,
+    pending: VecDeque<usize>,
+    status: Vec<MapdStatus<S>>,
+    planned: Vec<bool>,
+}
+
+impl<TS, S, A, C, DC, H> Mapd<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC> + Send + Sync,
+    S: Debug + State + Eq + Hash + Clone + Send + Sync,
+    A: Debug + Copy + Send + Sync,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues
+        + Send
+        + Sync,
+    DC: Debug
+        + Hash
+        + Ord
+        + Add<DC, Output = DC>
+        + Sub<DC, Output = DC>
+        + Div<f64, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
+    H: Heuristic<TS, S, A, C, DC> + HeuristicBuilder<TS, S, A, C, DC> + Send + Sync,
+{
+    /// Creates a new pickup and delivery layer over the given planner, whose agents are all idle.
+    ///
+    /// # Arguments
+    ///
+    /// * `planner` - The lifelong planner, with the agents at their initial position.
+    /// * `policy` - The policy used to assign the tasks to the agents.
+    /// * `parking` - The parking endpoints at which the agents without task wait.
+    pub fn new(planner: Lifelong<TS, S, A, C, DC, H>, policy: MapdPolicy, parking: Vec<S>) -> Self {
+        let n_agents = planner.get_solutions().len();
+        Self {
+            planner,
+            policy,
+            parking,
+            tasks: vec![],
+            records: vec![],
+            pending: VecDeque::new(),
+            status: vec![MapdStatus::Idle; n_agents],
+            planned: vec![true; n_agents],
+        }
+    }
+
+    /// Adds a task to the pending tasks, and returns its identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task, which is not assigned before its release time.
+    pub fn add_task(&mut self, task: PickupDeliveryTask<S, C>) -> usize {
+        self.records.push(ServiceRecord {
+            agent: None,
+            release: task.release,
+            pickup: None,
+            delivery: None,
+            failed: false,
+        });
+        self.tasks.push(task);
+        self.pending.push_back(self.tasks.len() - 1);
+        self.tasks.len() - 1
+    }
+
+    /// Advances the planner to the given time, records the pickups and deliveries performed
+    /// since the last update, releases the tasks of the agents that broke down since then,
+    /// assigns the released tasks to the idle agents, and plans
+    /// the new legs of the agents. Agents whose leg could not be planned are retried
+    /// at the next update.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The current time.
+    /// * `n_threads` - The number of threads to use.
+    /// * `time_limit` - The time limit of the planning request.
+    pub fn update(&mut self, time: C, n_threads: usize, time_limit: Option<Duration>) {
+        self.record_arrivals(time);
+        self.release_disabled();
+        self.planner.advance_to(time);
+
+        match self.policy {
+            MapdPolicy::Greedy => self.assign_greedy(time),
+            MapdPolicy::TokenPassing => self.assign_token_passing(time),
+        }
+        self.assign_parking();
+
+        let goals = (0..self.status.len())
+            .filter(|agent| !self.planned[*agent])
+            .filter_map(|agent| self.get_target(agent).map(|target| (agent, target)))
+            .collect::<FxHashMap<_, _>>();
+        if goals.is_empty() {
+            return;
+        }
+
+        let request = LifelongRequest {
+            goals,
+            time,
+            n_threads,
+            time_limit,
+        };
+        let rejected = self.planner.solve_request(&request).rejected;
+        for agent in request.goals.keys() {
+            if !rejected.contains(agent) {
+                self.planned[*agent] = true;
+            }
+        }
+    }
+
+    /// Returns the underlying lifelong planner.
+    pub fn get_planner(&self) -> &Lifelong<TS, S, A, C, DC, H> {
+        &self.planner
+    }
+
+    /// Returns the underlying lifelong planner, for instance to mark an agent as broken down.
+    pub fn get_planner_mut(&mut self) -> &mut Lifelong<TS, S, A, C, DC, H> {
+        &mut self.planner
+    }
+
+    /// Returns the current activity of the given agent.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent.
+    pub fn get_status(&self, agent: usize) -> &MapdStatus<S> {
+        &self.status[agent]
+    }
+
+    /// Returns the service record of each task, indexed by its identifier.
+    pub fn get_records(&self) -> &[ServiceRecord<C>] {
+        &self.records
+    }
+
+    /// Returns the average time between the release and the delivery of the delivered tasks,
+    /// or None if no task has been delivered.
+    pub fn mean_service_time(&self) -> Option<DC> {
+        let service_times = self
+            .records
+            .iter()
+            .filter_map(|record| record.service_time())
+            .collect::<Vec<DC>>();
+        if service_times.is_empty() {
+            return None;
+        }
+        let total = service_times
+            .iter()
+            .fold(DC::default(), |total, service_time| total + *service_time);
+        Some(total / service_times.len() as f64)
+    }
+
+    /// Records the pickups and deliveries of the agents that reached their target.
+    fn record_arrivals(&mut self, time: C) {
+        for agent in 0..self.status.len() {
+            if !self.planned[agent] {
+                continue;
+            }
+            let Some(target) = self.get_target(agent) else {
+                continue;
+            };
+            let (state, arrival) = self.planner.get_solutions()[agent].steps.last().unwrap();
+            if state.internal_state != target || *arrival > time {
+                continue;
+            }
+
+            match self.status[agent] {
+                MapdStatus::Pickup(task) => {
+                    self.records[task].pickup = Some(*arrival);
+                    self.set_status(agent, MapdStatus::Delivery(task));
+                }
+                MapdStatus::Delivery(task) => {
+                    self.records[task].delivery = Some(*arrival);
+                    self.status[agent] = MapdStatus::Idle;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Puts the tasks that the broken down agents have not picked up back in the pending tasks,
+    /// and marks the tasks that they carry as failed.
+    fn release_disabled(&mut self) {
+        for agent in 0..self.status.len() {
+            if !self.planner.is_disabled(agent) || self.status[agent] == MapdStatus::Disabled {
+                continue;
+            }
+
+            match self.status[agent] {
+                MapdStatus::Pickup(task) => {
+                    self.records[task].agent = None;
+                    let index = self.pending.partition_point(|pending| *pending < task);
+                    self.pending.insert(index, task);
+                }
+                MapdStatus::Delivery(task) => self.records[task].failed = true,
+                _ => {}
+            }
+            self.status[agent] = MapdStatus::Disabled;
+            self.planned[agent] = true;
+        }
+    }
+
+    /// Assigns each released task to the nearest idle agent for which its pickup
+    /// and delivery states are not the endpoint of another agent.
+    fn assign_greedy(&mut self, time: C) {
+        for task in self.get_released(time) {
+            let pickup = self.tasks[task].pickup.clone();
+            let delivery = &self.tasks[task].delivery;
+            let candidates = self
+                .get_idle_agents()
+                .into_iter()
+                .filter(|agent| {
+                    let endpoints = self.get_endpoints(*agent);
+                    !endpoints.contains(&pickup) && !endpoints.contains(delivery)
+                })
+                .collect::<Vec<_>>();
+            let nearest = candidates
+                .into_iter()
+                .filter_map(|agent| {
+                    let state = self.get_current_state(agent);
+                    self.planner
+                        .get_distance(&state, &pickup)
+                        .map(|distance| (distance, agent))
+                })
+                .min();
+            if let Some((_, agent)) = nearest {
+                self.assign(agent, task);
+            }
+        }
+    }
+
+    /// Lets each idle agent take the nearest released task whose pickup and delivery states
+    /// are not the endpoint of another agent.
+    fn assign_token_passing(&mut self, time: C) {
+        for agent in self.get_idle_agents() {
+            let endpoints = self.get_endpoints(agent);
+            let state = self.get_current_state(agent);
+            let candidates = self
+                .get_released(time)
+                .into_iter()
+                .filter(|task| {
+                    !endpoints.contains(&self.tasks[*task].pickup)
+                        && !endpoints.contains(&self.tasks[*task].delivery)
+                })
+                .collect::<Vec<_>>();
+            let nearest = candidates
+                .into_iter()
+                .filter_map(|task| {
+                    let pickup = self.tasks[task].pickup.clone();
+                    self.planner
+                        .get_distance(&state, &pickup)
+                        .map(|distance| (distance, task))
+                })
+                .min();
+            if let Some((_, task)) = nearest {
+                self.assign(agent, task);
+            }
+        }
+    }
+
+    /// Sends the agents without task nor parking endpoint to the nearest free parking endpoint.
+    fn assign_parking(&mut self) {
+        for agent in 0..self.status.len() {
+            if self.status[agent] != MapdStatus::Idle || !self.is_available(agent) {
+                continue;
+            }
+            let endpoints = self.get_endpoints(agent);
+            let state = self.get_current_state(agent);
+            let nearest = self
+                .parking
+                .clone()
+                .into_iter()
+                .filter(|parking| !endpoints.contains(parking))
+                .filter_map(|parking| {
+                    self.planner
+                        .get_distance(&state, &parking)
+                        .map(|distance| (distance, parking))
+                })
+                .min_by_key(|(distance, _)| *distance);
+            if let Some((_, parking)) = nearest {
+                self.set_status(agent, MapdStatus::Parking(parking));
+            }
+        }
+    }
+
+    fn assign(&mut self, agent: usize, task: usize) {
+        self.pending.retain(|t| *t != task);
+        self.records[task].agent = Some(agent);
+        self.set_status(agent, MapdStatus::Pickup(task));
+    }
+
+    /// Sets the status of the agent, which needs a new path unless its current one
+    /// already ends at its new target.
+    fn set_status(&mut self, agent: usize, status: MapdStatus<S>) {
+        self.status[agent] = status;
+        let last = &self.planner.get_solutions()[agent].steps.last().unwrap().0;
+        self.planned[agent] = self.get_target(agent) == Some(last.internal_state.clone());
+    }
+
+    /// Returns the state that the agent must reach, if any.
+    fn get_target(&self, agent: usize) -> Option<S> {
+        match &self.status[agent] {
+            MapdStatus::Idle | MapdStatus::Disabled => None,
+            MapdStatus::Parking(parking) => Some(parking.clone()),
+            MapdStatus::Pickup(task) => Some(self.tasks[*task].pickup.clone()),
+            MapdStatus::Delivery(task) => Some(self.tasks[*task].delivery.clone()),
+        }
+    }
+
+    /// Returns the final states of the other agents, in which they stay indefinitely.
+    /// The final state of a broken down agent is the position at which it stopped.
+    fn get_endpoints(&self, agent: usize) -> Vec<S> {
+        (0..self.status.len())
+            .filter(|other| *other != agent && self.planner.is_active(*other))
+            .map(|other| {
+                self.get_target(other).unwrap_or_else(|| {
+                    let last = &self.planner.get_solutions()[other].steps.last().unwrap().0;
+                    last.internal_state.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the state at which the agent currently is, or that it is leaving.
+    fn get_current_state(&self, agent: usize) -> S {
+        self.planner.get_solutions()[agent].steps[0]
+            .0
+            .internal_state
+            .clone()
+    }
+
+    fn get_released(&self, time: C) -> Vec<usize> {
+        self.pending
+            .iter()
+            .filter(|task| self.tasks[**task].release <= time)
+            .copied()
+            .collect()
+    }
+
+    fn get_idle_agents(&self) -> Vec<usize> {
+        (0..self.status.len())
+            .filter(|agent| {
+                matches!(
+                    self.status[*agent],
+                    MapdStatus::Idle | MapdStatus::Parking(_)
+                ) && self.is_available(*agent)
+            })
+            .collect()
+    }
+
+    fn is_available(&self, agent: usize) -> bool {
+        self.planner.is_active(agent) && !self.planner.is_disabled(agent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, GraphEdgeId, GraphNodeId, Lifelong, Mapd, MapdPolicy, MapdStatus,
+        PickupDeliveryTask, SimpleHeuristic, SimpleState, SimpleWorld,
+    };
+
+    fn get_mapd(
+        initial_states: Vec<usize>,
+        policy: MapdPolicy,
+        parking: Vec<usize>,
+    ) -> Mapd<
+        SimpleWorld,
+        SimpleState,
+        GraphEdgeId,
+        OrderedFloat<f64>,
+        OrderedFloat<f64>,
+        SimpleHeuristic,
+    > {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        let planner = Lifelong::new(
+            transition_system,
+            initial_states
+                .into_iter()
+                .map(|n| SimpleState(GraphNodeId(n)))
+                .collect(),
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        );
+        Mapd::new(
+            planner,
+            policy,
+            parking
+                .into_iter()
+                .map(|n| SimpleState(GraphNodeId(n)))
+                .collect(),
+        )
+    }
+
+    fn task(
+        pickup: usize,
+        delivery: usize,
+        release: f64,
+    ) -> PickupDeliveryTask<SimpleState, OrderedFloat<f64>> {
+        PickupDeliveryTask {
+            pickup: SimpleState(GraphNodeId(pickup)),
+            delivery: SimpleState(GraphNodeId(delivery)),
+            release: OrderedFloat(release),
+        }
+    }
+
+    #[test]
+    fn test_greedy() {
+        let mut mapd = get_mapd(vec![0, 99], MapdPolicy::Greedy, vec![]);
+        let first = mapd.add_task(task(95, 99, 0.0));
+        let second = mapd.add_task(task(9, 0, 5.0));
+
+        for time in 0..=40 {
+            mapd.update(OrderedFloat(time as f64), 1, None);
+            if time == 0 {
+                assert_eq!(mapd.get_status(1), &MapdStatus::Pickup(first));
+                assert_eq!(mapd.get_status(0), &MapdStatus::Idle);
+            } else if time == 5 {
+                assert_eq!(mapd.get_status(0), &MapdStatus::Pickup(second));
+            }
+        }
+
+        let records = mapd.get_records();
+        assert_eq!(records[first].agent, Some(1));
+        assert_eq!(records[first].pickup, Some(OrderedFloat(4.0)));
+        assert_eq!(records[first].delivery, Some(OrderedFloat(8.0)));
+        assert_eq!(records[second].agent, Some(0));
+        assert_eq!(records[second].pickup, Some(OrderedFloat(14.0)));
+        assert_eq!(records[second].delivery, Some(OrderedFloat(23.0)));
+        assert_eq!(mapd.mean_service_time(), Some(OrderedFloat(13.0)));
+        assert_eq!(mapd.get_status(0), &MapdStatus::Idle);
+
+        // The nearest agent cannot deliver to the state where the other agent stays
+        let mut mapd = get_mapd(vec![0, 99], MapdPolicy::Greedy, vec![]);
+        let blocked = mapd.add_task(task(50, 99, 0.0));
+        mapd.update(OrderedFloat(0.0), 1, None);
+        assert_eq!(mapd.get_status(0), &MapdStatus::Idle);
+        assert_eq!(mapd.get_status(1), &MapdStatus::Pickup(blocked));
+
+        for time in 1..=30 {
+            mapd.update(OrderedFloat(time as f64), 1, None);
+        }
+        assert_eq!(mapd.get_records()[blocked].agent, Some(1));
+        assert_eq!(
+            mapd.get_records()[blocked].delivery,
+            Some(OrderedFloat(26.0))
+        );
+    }
+
+    #[test]
+    fn test_token_passing() {
+        let mut mapd = get_mapd(vec![0, 99], MapdPolicy::TokenPassing, vec![55, 44]);
+        // The delivery state of the first task is the endpoint of the second agent
+        let blocked = mapd.add_task(task(90, 99, 0.0));
+        let free = mapd.add_task(task(9, 5, 0.0));
+
+        mapd.update(OrderedFloat(0.0), 1, None);
+        assert_eq!(mapd.get_status(0), &MapdStatus::Pickup(free));
+        assert_eq!(mapd.get_status(1), &MapdStatus::Pickup(blocked));
+
+        for time in 1..=60 {
+            mapd.update(OrderedFloat(time as f64), 1, None);
+        }
+
+        assert!(mapd.get_records().iter().all(|r| r.delivery.is_some()));
+        let parked = (0..2)
+            .map(|agent| mapd.get_status(agent).clone())
+            .collect::<Vec<_>>();
+        assert_ne!(parked[0], parked[1]);
+        for (agent, status) in parked.iter().enumerate() {
+            let MapdStatus::Parking(parking) = status else {
+                panic!("agent {agent} is not parking");
+            };
+            let solution = &mapd.get_planner().get_solutions()[agent];
+            assert_eq!(&solution.steps.last().unwrap().0.internal_state, parking);
+        }
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut mapd = get_mapd(vec![0, 99], MapdPolicy::TokenPassing, vec![]);
+        let first = mapd.add_task(task(5, 95, 0.0));
+        for time in 0..=2 {
+            mapd.update(OrderedFloat(time as f64), 1, None);
+        }
+        assert_eq!(mapd.get_status(0), &MapdStatus::Pickup(first));
+
+        // The first agent breaks down on its way to the pickup state, and the task is given
+        // to the second agent, the pickup state not being an endpoint anymore
+        mapd.get_planner_mut()
+            .mark_disabled(0, OrderedFloat(2.5), 1, None);
+        let mut time = 3;
+        while mapd.get_records()[first].delivery.is_none() {
+            assert!(time <= 60);
+            mapd.update(OrderedFloat(time as f64), 1, None);
+            time += 1;
+        }
+        assert_eq!(mapd.get_status(0), &MapdStatus::Disabled);
+        assert_eq!(mapd.get_records()[first].agent, Some(1));
+
+        // The second agent breaks down while carrying a task
+        let second = mapd.add_task(task(50, 59, time as f64));
+        while mapd.get_status(1) != &MapdStatus::Delivery(second) {
+            assert!(time <= 120);
+            mapd.update(OrderedFloat(time as f64), 1, None);
+            time += 1;
+        }
+        mapd.get_planner_mut()
+            .mark_disabled(1, OrderedFloat(time as f64 - 0.5), 1, None);
+        mapd.update(OrderedFloat(time as f64), 1, None);
+
+        let record = mapd.get_records()[second];
+        assert!(record.failed);
+        assert!(record.delivery.is_none());
+        assert_eq!(mapd.get_status(1), &MapdStatus::Disabled);
+        assert_eq!(
+            mapd.mean_service_time(),
+            mapd.get_records()[first].service_time()
+        );
+    }
+}
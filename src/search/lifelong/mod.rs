@@ -1,3 +1,9 @@
+mod cache;
 mod lifelong;
+mod mapd;
+mod simulation;
 
+pub use cache::*;
 pub use lifelong::*;
+pub use mapd::*;
+pub use simulation::*;
@@ -0,0 +1,401 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Heuristic, HeuristicBuilder, Lifelong, LifelongRequest, MyTime, State, TransitionSystem,
+};
+
+/// A stream of goals given to the agents of a lifelong simulation.
+pub trait GoalGenerator<S> {
+    /// Returns the next goal of the given agent, which is currently at the given state,
+    /// or None if the agent has no more tasks.
+    fn next_goal(&mut self, agent: usize, state: &S) -> Option<S>;
+}
+
+/// Generates goals drawn uniformly at random among a set of states,
+/// each goal being different from the current state of the agent.
+pub struct RandomGoals<S> {
+    states: Vec<S>,
+    random: SplitMix64,
+}
+
+impl<S> RandomGoals<S> {
+    /// Creates a new generator of random goals.
+    ///
+    /// # Arguments
+    ///
+    /// * `states` - The states among which the goals are drawn.
+    /// * `seed` - The seed of the pseudo-random number generator.
+    pub fn new(states: Vec<S>, seed: u64) -> Self {
+        Self {
+            states,
+            random: SplitMix64(seed),
+        }
+    }
+}
+
+impl<S> GoalGenerator<S> for RandomGoals<S>
+where
+    S: Eq + Clone,
+{
+    fn next_goal(&mut self, _agent: usize, state: &S) -> Option<S> {
+        if self.states.iter().all(|s| s == state) {
+            return None;
+        }
+        loop {
+            let goal = &self.states[self.random.below(self.states.len())];
+            if goal != state {
+                return Some(goal.clone());
+            }
+        }
+    }
+}
+
+/// Gives a fixed sequence of goals to each agent.
+pub struct ScriptedGoals<S> {
+    goals: Vec<VecDeque<S>>,
+}
+
+impl<S> ScriptedGoals<S> {
+    /// Creates a new generator of scripted goals.
+    ///
+    /// # Arguments
+    ///
+    /// * `goals` - The sequence of goals of each agent.
+    pub fn new(goals: Vec<Vec<S>>) -> Self {
+        Self {
+            goals: goals.into_iter().map(VecDeque::from).collect(),
+        }
+    }
+}
+
+impl<S> GoalGenerator<S> for ScriptedGoals<S> {
+    fn next_goal(&mut self, agent: usize, _state: &S) -> Option<S> {
+        self.goals.get_mut(agent)?.pop_front()
+    }
+}
+
+/// A small pseudo-random number generator, which makes simulations reproducible.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// The configuration of a lifelong simulation.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// The time at which the simulation starts.
+    pub start: MyTime,
+    /// The time at which the simulation ends.
+    pub end: MyTime,
+    /// The time between two planning requests, which must be positive.
+    pub period: MyTime,
+    /// The number of threads to use.
+    pub n_threads: usize,
+    /// The time limit of each planning request.
+    pub time_limit: Option<Duration>,
+}
+
+/// The statistics collected during a lifelong simulation.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationStats {
+    /// The simulated duration.
+    pub duration: f64,
+    /// The number of completed tasks.
+    pub completed_tasks: usize,
+    /// The number of tasks rejected at least once by the planner, which are submitted again
+    /// in the next requests until they are accepted.
+    pub rejected_tasks: usize,
+    /// The wall time of each planning request.
+    pub planning_times: Vec<Duration>,
+    /// The total time spent waiting by the agents while performing a task.
+    pub wait_time: f64,
+}
+
+impl SimulationStats {
+    /// Returns the number of tasks completed per hour, the time being expressed in seconds.
+    pub fn throughput(&self) -> f64 {
+        if self.duration > 0.0 {
+            self.completed_tasks as f64 * 3600.0 / self.duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the given percentile of the planning times, or None if no request was made.
+    ///
+    /// # Arguments
+    ///
+    /// * `percentile` - The percentile, between 0 and 100.
+    pub fn planning_time_percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut times = self.planning_times.clone();
+        times.sort_unstable();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * times.len() as f64).ceil() as usize;
+        times.get(rank.max(1) - 1).copied()
+    }
+
+    /// Returns the average time spent waiting per completed task.
+    pub fn mean_wait_time(&self) -> f64 {
+        if self.completed_tasks > 0 {
+            self.wait_time / self.completed_tasks as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Runs a lifelong simulation, in which the agents are given new goals when they reach
+/// their current goal. At each period, the time of the planner is advanced, and a planning
+/// request is made for the agents that need a new goal. Returns the collected statistics.
+/// Panics if the period of the simulation is not positive.
+///
+/// # Arguments
+///
+/// * `planner` - The lifelong planner, with the agents at their initial position.
+/// * `goals` - The stream of goals given to the agents.
+/// * `config` - The configuration of the simulation.
+pub fn simulate<TS, S, A, H, G>(
+    planner: &mut Lifelong<TS, S, A, MyTime, MyTime, H>,
+    goals: &mut G,
+    config: &SimulationConfig,
+) -> SimulationStats
+where
+    TS: TransitionSystem<S, A, MyTime, MyTime> + Send + Sync,
+    S: Debug + State + Eq + Hash + Clone + Send + Sync,
+    A: Debug + Copy + Send + Sync,
+    H: Heuristic<TS, S, A, MyTime, MyTime>
+        + HeuristicBuilder<TS, S, A, MyTime, MyTime>
+        + Send
+        + Sync,
+    G: GoalGenerator<S>,
+{
+    assert!(
+        config.period.0 > 0.0,
+        "the period must be positive, got {}",
+        config.period
+    );
+
+    let mut stats = SimulationStats {
+        duration: (config.end - config.start).0,
+        ..Default::default()
+    };
+
+    let n_agents = planner.get_solutions().len();
+    // Whether each agent is performing a task, and the goal it waits for
+    let mut busy = vec![false; n_agents];
+    let mut pending: Vec<Option<S>> = vec![None; n_agents];
+    // Whether the pending goal of each agent has already been rejected
+    let mut rejected = vec![false; n_agents];
+
+    let mut previous = config.start;
+    let mut time = config.start;
+    loop {
+        // Collect the waits performed since the last request, and the completed tasks
+        for (agent, solution) in planner.get_solutions().iter().enumerate() {
+            if !busy[agent] {
+                continue;
+            }
+
+            let arrival = solution.steps.last().unwrap().1;
+            for (i, action) in solution.actions.iter().enumerate() {
+                let start = solution.steps[i].1.max(previous);
+                let end = solution.steps[i + 1].1.min(time.min(arrival));
+                if action.action.is_none() && start < end {
+                    stats.wait_time += (end - start).0;
+                }
+            }
+
+            if arrival <= time {
+                stats.completed_tasks += 1;
+                busy[agent] = false;
+            }
+        }
+
+        if time >= config.end {
+            break;
+        }
+
+        planner.advance_to(time);
+
+        // Give new goals to the idle agents
+        let solutions = planner.get_solutions();
+        for agent in 0..n_agents {
+            if busy[agent]
+                || pending[agent].is_some()
+                || !planner.is_active(agent)
+                || planner.is_disabled(agent)
+            {
+                continue;
+            }
+            let state = &solutions[agent].steps.last().unwrap().0.internal_state;
+            pending[agent] = goals.next_goal(agent, state);
+        }
+
+        let request = LifelongRequest {
+            goals: pending
+                .iter()
+                .enumerate()
+                .filter_map(|(agent, goal)| goal.clone().map(|goal| (agent, goal)))
+                .collect(),
+            time,
+            n_threads: config.n_threads,
+            time_limit: config.time_limit,
+        };
+        if !request.goals.is_empty() {
+            let start = Instant::now();
+            let outcome = planner.solve_request(&request);
+            stats.planning_times.push(start.elapsed());
+
+            for agent in request.goals.keys() {
+                if !outcome.rejected.contains(agent) {
+                    busy[*agent] = true;
+                    pending[*agent] = None;
+                    rejected[*agent] = false;
+                } else if !rejected[*agent] {
+                    stats.rejected_tasks += 1;
+                    rejected[*agent] = true;
+                }
+            }
+        }
+
+        previous = time;
+        time = (time + config.period).min(config.end);
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, simulate, GraphEdgeId, GraphNodeId, Lifelong, RandomGoals, ScriptedGoals,
+        SimpleHeuristic, SimpleState, SimpleWorld, SimulationConfig,
+    };
+
+    fn get_planner(
+        initial_states: Vec<SimpleState>,
+    ) -> Lifelong<
+        SimpleWorld,
+        SimpleState,
+        GraphEdgeId,
+        OrderedFloat<f64>,
+        OrderedFloat<f64>,
+        SimpleHeuristic,
+    > {
+        let transition_system = Arc::new(SimpleWorld::new(simple_graph(10), 0.4));
+        Lifelong::new(
+            transition_system,
+            initial_states,
+            OrderedFloat(0.0),
+            OrderedFloat(1e-6),
+        )
+    }
+
+    #[test]
+    fn test_scripted() {
+        let mut planner = get_planner(vec![SimpleState(GraphNodeId(0))]);
+        let mut goals = ScriptedGoals::new(vec![vec![
+            SimpleState(GraphNodeId(9)),
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(9)),
+        ]]);
+        let config = SimulationConfig {
+            start: OrderedFloat(0.0),
+            end: OrderedFloat(20.0),
+            period: OrderedFloat(1.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+
+        let stats = simulate(&mut planner, &mut goals, &config);
+        assert_eq!(stats.completed_tasks, 2);
+        assert_eq!(stats.planning_times.len(), 3);
+        assert_eq!(stats.wait_time, 0.0);
+        assert_eq!(stats.throughput(), 360.0);
+    }
+
+    #[test]
+    fn test_rejected() {
+        // The goal of the first agent is occupied forever by the second agent
+        let mut planner = get_planner(vec![
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(9)),
+        ]);
+        let mut goals = ScriptedGoals::new(vec![vec![SimpleState(GraphNodeId(9))], vec![]]);
+        let config = SimulationConfig {
+            start: OrderedFloat(0.0),
+            end: OrderedFloat(5.0),
+            period: OrderedFloat(1.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+
+        // The task is submitted at each period, but only counted once
+        let stats = simulate(&mut planner, &mut goals, &config);
+        assert_eq!(stats.planning_times.len(), 5);
+        assert_eq!(stats.rejected_tasks, 1);
+        assert_eq!(stats.completed_tasks, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the period must be positive")]
+    fn test_invalid_period() {
+        let mut planner = get_planner(vec![SimpleState(GraphNodeId(0))]);
+        let mut goals = ScriptedGoals::new(vec![vec![SimpleState(GraphNodeId(9))]]);
+        let config = SimulationConfig {
+            start: OrderedFloat(0.0),
+            end: OrderedFloat(5.0),
+            period: OrderedFloat(0.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+        simulate(&mut planner, &mut goals, &config);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut planner = get_planner(vec![
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(55)),
+            SimpleState(GraphNodeId(99)),
+        ]);
+        let mut goals =
+            RandomGoals::new((0..100).map(|i| SimpleState(GraphNodeId(i))).collect(), 42);
+        let config = SimulationConfig {
+            start: OrderedFloat(0.0),
+            end: OrderedFloat(100.0),
+            period: OrderedFloat(2.0),
+            n_threads: 1,
+            time_limit: None,
+        };
+
+        let stats = simulate(&mut planner, &mut goals, &config);
+        assert!(stats.completed_tasks >= 3);
+        assert_eq!(stats.rejected_tasks, 0);
+        assert!(stats.planning_time_percentile(50.0) <= stats.planning_time_percentile(95.0));
+        assert_eq!(
+            stats.planning_time_percentile(100.0),
+            stats.planning_times.iter().max().copied()
+        );
+        assert!(stats.mean_wait_time() >= 0.0);
+    }
+}
@@ -1,12 +1,16 @@
 mod cbs;
+mod cbsta;
 mod internal;
 mod lifelong;
 mod rra;
 mod sipp;
+mod validation;
 
 use internal::*;
 
 pub use cbs::*;
+pub use cbsta::*;
 pub use lifelong::*;
 pub use rra::*;
 pub use sipp::*;
+pub use validation::*;
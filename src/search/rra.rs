@@ -228,6 +228,7 @@ where
 
 /// Statistics of the Reverse Resumable A* algorithm.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RraStats {
     /// The number of new queries.
     pub new_query: usize,
@@ -385,6 +385,7 @@ where
 
 /// Statistics of the Safe Interval Path Planning algorithm with landmarks.
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LSippStats {
     /// The number of searches performed.
     pub searches: usize,
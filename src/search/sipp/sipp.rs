@@ -15,7 +15,7 @@ use std::{
 use fxhash::{FxHashMap, FxHashSet};
 
 use crate::{
-    search::{ConstraintSet, SearchNode},
+    search::{unify, Constraint, ConstraintSet, SearchNode},
     Action, Heuristic, Interval, LimitValues, Solution, State, Task, TransitionSystem,
 };
 
@@ -230,6 +230,7 @@ where
             }
 
             if config.task.is_goal(&current)
+                && self.can_wait_for_goal_interval(config, &current)
                 && self.goal_intervals.remove(&current.state.safe_interval)
             {
                 // A path to the goal has been found
@@ -256,10 +257,12 @@ where
         config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
         current: &SearchNode<SippState<S, C, DC>, C, DC>,
     ) {
-        for action in self
+        let max_waiting_time = self
             .transition_system
-            .actions_from(&current.state.internal_state)
-        {
+            .max_waiting_time(&current.state.internal_state);
+
+        let transition_system = self.transition_system.clone();
+        for action in transition_system.actions_from(&current.state.internal_state) {
             let successor_state = self
                 .transition_system
                 .transition(&current.state.internal_state, action);
@@ -282,6 +285,24 @@ where
                 .constraints
                 .get_action_constraints(&current.state.internal_state, &successor_state);
 
+            // Departing during a closure of the action, or shortly before it, is also forbidden
+            let mut blocked_departures;
+            let action_constraints = if let Some(action_closures) = config
+                .constraints
+                .get_action_closures(&current.state.internal_state, &successor_state)
+            {
+                blocked_departures = action_constraints.cloned().unwrap_or_default();
+                blocked_departures.extend(action_closures.iter().map(|closure| {
+                    let mut closure = closure.clone();
+                    closure.interval.start = closure.interval.start - transition_cost;
+                    closure
+                }));
+                unify(&mut blocked_departures);
+                Some(&blocked_departures)
+            } else {
+                action_constraints
+            };
+
             // Try to reach any of the safe intervals of the destination state
             // and add the corresponding successors to the queue if a better path has been found
             Self::get_safe_intervals(
@@ -291,8 +312,10 @@ where
                 config.precision,
                 &mut self.safe_intervals,
             );
-            for safe_interval in self.safe_intervals.drain(..) {
+            let mut safe_intervals = std::mem::take(&mut self.safe_intervals);
+            for safe_interval in safe_intervals.drain(..) {
                 let mut successor_cost = current.cost + transition_cost;
+                let mut parent = current.state.clone();
 
                 if successor_cost + config.precision > safe_interval.end {
                     // Cannot reach this safe interval in time
@@ -361,6 +384,22 @@ where
                     }
                 }
 
+                if let Some(max_waiting_time) = max_waiting_time {
+                    if successor_cost - transition_cost
+                        > current.cost + max_waiting_time + config.precision
+                    {
+                        // Would wait too long at the current state, try to arrive there later instead
+                        match self.delay_arrival(
+                            config,
+                            &current.state,
+                            successor_cost - transition_cost - max_waiting_time,
+                        ) {
+                            Some(delayed) => parent = delayed,
+                            None => continue,
+                        }
+                    }
+                }
+
                 if successor_cost + heuristic >= self.goal_horizon {
                     // The remaining safe intervals at the goal state are not reachable in time
                     continue;
@@ -395,12 +434,89 @@ where
                 if improved {
                     self.parent.insert(
                         successor.state.clone(),
-                        (Action::new(*action, transition_cost), current.state.clone()),
+                        (Action::new(*action, transition_cost), parent),
                     );
                     self.queue.push(Reverse(successor))
                 }
             }
+            self.safe_intervals = safe_intervals;
+        }
+    }
+
+    /// Returns a copy of the given state that is reached at the given later time,
+    /// by waiting longer at its parent state, if the rules and constraints allow it.
+    fn delay_arrival(
+        &mut self,
+        config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
+        state: &Arc<SippState<S, C, DC>>,
+        arrival: C,
+    ) -> Option<Arc<SippState<S, C, DC>>> {
+        let (action, parent) = self.parent.get(state).cloned()?;
+        let departure = arrival - action.cost;
+
+        if !self.transition_system.can_wait_at(&parent.internal_state)
+            || departure + config.precision > parent.safe_interval.end
+            || self
+                .transition_system
+                .max_waiting_time(&parent.internal_state)
+                .is_some_and(|max_waiting_time| {
+                    departure > self.distance[&parent] + max_waiting_time + config.precision
+                })
+        {
+            // Cannot wait long enough at the parent state
+            return None;
+        }
+
+        // Check collision along the action, or with a closure of the action
+        let collides = |constraints: Option<&Vec<Constraint<S, C, DC>>>, duration: DC| {
+            constraints.is_some_and(|constraints| {
+                constraints.iter().any(|constraint| {
+                    departure + config.precision + duration >= constraint.interval.start
+                        && departure <= constraint.interval.end
+                })
+            })
+        };
+        let constraints = &config.constraints;
+        if collides(
+            constraints.get_action_constraints(&parent.internal_state, &state.internal_state),
+            DC::default(),
+        ) || collides(
+            constraints.get_action_closures(&parent.internal_state, &state.internal_state),
+            action.cost,
+        ) {
+            return None;
+        }
+
+        let delayed = Arc::new(SippState {
+            safe_interval: Interval::new(arrival, state.safe_interval.end),
+            internal_state: state.internal_state.clone(),
+        });
+        self.distance.insert(delayed.clone(), arrival);
+        self.parent.insert(delayed.clone(), (action, parent));
+
+        Some(delayed)
+    }
+
+    /// Returns true if the agent can wait at the goal state from the given search node
+    /// until the beginning of the goal interval.
+    fn can_wait_for_goal_interval(
+        &self,
+        config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
+        goal: &SearchNode<SippState<S, C, DC>, C, DC>,
+    ) -> bool {
+        if goal.cost >= config.task.goal_interval.start {
+            return true;
         }
+
+        let state = &goal.state.internal_state;
+        self.transition_system.can_wait_at(state)
+            && self
+                .transition_system
+                .max_waiting_time(state)
+                .is_none_or(|max_waiting_time| {
+                    config.task.goal_interval.start
+                        <= goal.cost + max_waiting_time + config.precision
+                })
     }
 
     /// Computes the safe intervals for the given state, given a set of constraints,
@@ -630,6 +746,7 @@ where
 /// State wrapper for the Safe Interval Path Planning algorithm that extends
 /// a given state definition with a safe interval.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SippState<S, C, DC>
 where
     S: Debug + Eq,
@@ -718,6 +835,7 @@ where
 
 /// Statistics of the Safe Interval Path Planning algorithm.
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SippStats {
     /// The number of searches performed.
     pub searches: usize,
@@ -740,8 +858,8 @@ mod tests {
 
     use crate::{
         search::{Constraint, ConstraintSet},
-        simple_graph, GraphEdgeId, GraphNodeId, Interval, MyTime, ReverseResumableAStar,
-        SimpleHeuristic, SimpleState, SimpleWorld, SippConfig, Task,
+        simple_graph, Graph, GraphEdgeId, GraphNodeId, Interval, MyTime, ReverseResumableAStar,
+        SimpleHeuristic, SimpleNodeData, SimpleState, SimpleWorld, SippConfig, Task,
     };
 
     use super::SafeIntervalPathPlanning;
@@ -889,4 +1007,186 @@ mod tests {
 
         assert_eq!(solution.cost, OrderedFloat(24.0));
     }
+
+    #[test]
+    fn test_with_closures() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(size - 1)),
+            OrderedFloat(0.0),
+        ));
+
+        // Short closure: waiting for the action to reopen is better than a detour
+        // Long closure: a detour is better than waiting
+        for (end, cost) in [(OrderedFloat(5.0), 10.0), (OrderedFloat(10.0), 11.0)] {
+            let mut constraints = ConstraintSet::default();
+            constraints.add(&Arc::new(Constraint::new_closure(
+                0,
+                SimpleState(GraphNodeId(4)),
+                SimpleState(GraphNodeId(5)),
+                Interval::new(OrderedFloat(2.0), end),
+            )));
+
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Arc::new(constraints),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+
+            let solution = solver.solve(&config).unwrap();
+
+            assert_eq!(solution.cost, OrderedFloat(cost));
+        }
+    }
+
+    #[test]
+    fn test_with_waiting_rules() {
+        // A direct path through (1, 0) and a detour through (1, 1)
+        let build_graph = |direct: SimpleNodeData| {
+            let mut graph = Graph::new();
+            let start = graph.add_node(SimpleNodeData {
+                no_wait: true,
+                ..(0.0, 0.0).into()
+            });
+            let direct = graph.add_node(direct);
+            let goal = graph.add_node((2.0, 0.0).into());
+            let detour = graph.add_node((1.0, 1.0).into());
+            for (from, to) in [
+                (start, direct),
+                (direct, goal),
+                (start, detour),
+                (detour, goal),
+            ] {
+                graph.add_edge(from, to, 1.0.into());
+            }
+            (Arc::new(graph), start, detour, goal)
+        };
+
+        for (direct, detour_expected) in [
+            (SimpleNodeData::from((1.0, 0.0)), false),
+            (
+                SimpleNodeData {
+                    no_wait: true,
+                    ..(1.0, 0.0).into()
+                },
+                true,
+            ),
+            (
+                SimpleNodeData {
+                    max_dwell: Some(1.0),
+                    ..(1.0, 0.0).into()
+                },
+                true,
+            ),
+        ] {
+            let (graph, start, detour, goal) = build_graph(direct);
+            let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+            let task = Arc::new(Task::new(
+                SimpleState(start),
+                SimpleState(goal),
+                OrderedFloat(0.0),
+            ));
+
+            // The goal is only available after some time, so the agent must wait somewhere
+            let mut constraints = ConstraintSet::default();
+            constraints.add(&Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(goal),
+                Interval::new(OrderedFloat(0.0), OrderedFloat(3.5)),
+            )));
+
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Arc::new(constraints),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+
+            let solution = solver.solve(&config).unwrap();
+
+            assert_eq!(solution.cost, OrderedFloat(3.5));
+            assert_eq!(
+                solution
+                    .steps
+                    .iter()
+                    .any(|step| step.0.internal_state.0 == detour),
+                detour_expected
+            );
+        }
+
+        // A single path, along which the agent must wait before reaching the node with a maximum dwell time
+        let mut graph = Graph::new();
+        let start = graph.add_node((0.0, 0.0).into());
+        let middle = graph.add_node(SimpleNodeData {
+            max_dwell: Some(0.5),
+            ..(1.0, 0.0).into()
+        });
+        let goal = graph.add_node((2.0, 0.0).into());
+        graph.add_edge(start, middle, 1.0.into());
+        graph.add_edge(middle, goal, 1.0.into());
+
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(start),
+            SimpleState(goal),
+            OrderedFloat(0.0),
+        ));
+
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(goal),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(3.5)),
+        )));
+
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Arc::new(constraints),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+
+        let solution = solver.solve(&config).unwrap();
+
+        assert_eq!(solution.cost, OrderedFloat(3.5));
+        assert_eq!(
+            solution
+                .steps
+                .iter()
+                .map(|step| (step.0.internal_state.0, step.1))
+                .collect::<Vec<_>>(),
+            vec![
+                (start, OrderedFloat(0.0)),
+                (start, OrderedFloat(1.0)),
+                (middle, OrderedFloat(2.0)),
+                (middle, OrderedFloat(2.5)),
+                (goal, OrderedFloat(3.5)),
+            ]
+        );
+    }
 }
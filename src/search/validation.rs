@@ -0,0 +1,456 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Sub},
+    sync::Arc,
+};
+
+use tuple::{A2, T2};
+
+use crate::{Interval, LimitValues, Move, SippState, Solution, State, Task, TransitionSystem};
+
+/// A rule of the problem that is not respected by a set of solutions.
+#[derive(Debug)]
+pub enum Violation<S, A, C, DC>
+where
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy,
+{
+    /// The solution of the agent is missing, empty, or its steps and actions do not match.
+    MalformedSolution { agent: usize },
+    /// The solution does not start at the initial state and cost of the task.
+    InvalidStart { agent: usize, state: S, cost: C },
+    /// The solution does not end at the goal state of the task.
+    InvalidGoal { agent: usize, state: S },
+    /// The action of the given step cannot be applied from its state, or does not lead to the next state.
+    InvalidAction {
+        agent: usize,
+        step: usize,
+        action: Option<A>,
+    },
+    /// The duration of the given step does not match the cost of its action.
+    InvalidDuration {
+        agent: usize,
+        step: usize,
+        expected: DC,
+        actual: DC,
+    },
+    /// The given step ends before it starts.
+    NegativeDuration { agent: usize, step: usize },
+    /// The agent waits at a state where waiting is not allowed.
+    ForbiddenWait { agent: usize, step: usize, state: S },
+    /// The agent waits longer than allowed at a state, in consecutive steps ending with the given one.
+    WaitTooLong {
+        agent: usize,
+        step: usize,
+        state: S,
+        duration: DC,
+        max_duration: DC,
+    },
+    /// The moves of two agents lead to a collision.
+    Conflict { moves: A2<Move<S, A, C, DC>> },
+}
+
+/// Checks that the given solutions solve the given tasks, independently of the algorithm that found them,
+/// and returns all violated rules. The solutions are valid if the returned vector is empty.
+/// Each solution must start at the initial state of its task and end at its goal state,
+/// apply valid actions with matching durations, and only wait where the transition system allows it.
+/// Finally, the solutions of each pair of agents must not conflict.
+///
+/// # Arguments
+///
+/// * `transition_system` - The transition system in which the tasks are defined
+/// * `tasks` - The task of each agent
+/// * `solutions` - The solution of each agent
+/// * `precision` - The tolerance to use when comparing durations
+pub fn validate_solutions<TS, S, A, C, DC>(
+    transition_system: &TS,
+    tasks: &[Arc<Task<S, C>>],
+    solutions: &[Solution<Arc<SippState<S, C, DC>>, A, C, DC>],
+    precision: DC,
+) -> Vec<Violation<S, A, C, DC>>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + State + Eq + Hash + Clone,
+    A: Copy + PartialEq,
+    C: Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Ord + Add<DC, Output = DC> + Copy + Default,
+{
+    let mut violations = vec![];
+
+    let mut valid_agents = vec![];
+    for (agent, task) in tasks.iter().enumerate() {
+        match solutions.get(agent) {
+            Some(solution)
+                if !solution.steps.is_empty()
+                    && solution.steps.len() == solution.actions.len() + 1 =>
+            {
+                validate_solution(
+                    transition_system,
+                    task,
+                    solution,
+                    agent,
+                    precision,
+                    &mut violations,
+                );
+                valid_agents.push(agent);
+            }
+            _ => violations.push(Violation::MalformedSolution { agent }),
+        }
+    }
+
+    for (i, &agent) in valid_agents.iter().enumerate() {
+        for &other in valid_agents[i + 1..].iter() {
+            if let Some(moves) = find_conflict(
+                transition_system,
+                T2((agent, &solutions[agent]), (other, &solutions[other])),
+            ) {
+                violations.push(Violation::Conflict { moves });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Checks the solution of a single agent, and adds the violated rules to the given vector.
+fn validate_solution<TS, S, A, C, DC>(
+    transition_system: &TS,
+    task: &Task<S, C>,
+    solution: &Solution<Arc<SippState<S, C, DC>>, A, C, DC>,
+    agent: usize,
+    precision: DC,
+    violations: &mut Vec<Violation<S, A, C, DC>>,
+) where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + State + Eq + Hash + Clone,
+    A: Copy + PartialEq,
+    C: Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Ord + Add<DC, Output = DC> + Copy + Default,
+{
+    let (first, start) = &solution.steps[0];
+    if first.internal_state != task.initial_state || *start != task.initial_cost {
+        violations.push(Violation::InvalidStart {
+            agent,
+            state: first.internal_state.clone(),
+            cost: *start,
+        });
+    }
+
+    let last = &solution.steps[solution.steps.len() - 1].0;
+    if !task.is_goal_state(&last.internal_state) {
+        violations.push(Violation::InvalidGoal {
+            agent,
+            state: last.internal_state.clone(),
+        });
+    }
+
+    // Total duration of the ongoing wait
+    let mut waiting_time = DC::default();
+
+    for (step, action) in solution.actions.iter().enumerate() {
+        let (from, start) = &solution.steps[step];
+        let (to, end) = &solution.steps[step + 1];
+        let (from, to) = (&from.internal_state, &to.internal_state);
+
+        if end < start {
+            violations.push(Violation::NegativeDuration { agent, step });
+            continue;
+        }
+
+        let Some(action) = action.action else {
+            if from != to {
+                violations.push(Violation::InvalidAction {
+                    agent,
+                    step,
+                    action: None,
+                });
+                continue;
+            }
+
+            if *start + precision >= *end {
+                continue; // Waiting for no time is always allowed
+            }
+
+            if !transition_system.can_wait_at(from) {
+                violations.push(Violation::ForbiddenWait {
+                    agent,
+                    step,
+                    state: from.clone(),
+                });
+            }
+
+            waiting_time = waiting_time + (*end - *start);
+            if let Some(max_duration) = transition_system.max_waiting_time(from) {
+                let next_is_wait = solution
+                    .actions
+                    .get(step + 1)
+                    .is_some_and(|next| next.action.is_none());
+                if !next_is_wait && waiting_time > max_duration + precision {
+                    violations.push(Violation::WaitTooLong {
+                        agent,
+                        step,
+                        state: from.clone(),
+                        duration: waiting_time,
+                        max_duration,
+                    });
+                }
+            }
+            continue;
+        };
+
+        waiting_time = DC::default();
+
+        if !transition_system.actions_from(from).any(|a| *a == action)
+            || transition_system.transition(from, &action) != *to
+        {
+            violations.push(Violation::InvalidAction {
+                agent,
+                step,
+                action: Some(action),
+            });
+            continue;
+        }
+
+        let expected = transition_system.transition_cost(from, &action);
+        if *end + precision < *start + expected || *start + expected + precision < *end {
+            violations.push(Violation::InvalidDuration {
+                agent,
+                step,
+                expected,
+                actual: *end - *start,
+            });
+        }
+    }
+}
+
+/// Returns the first pair of conflicting moves in the solutions of the given agents, if any.
+/// Both agents are assumed to stay at their last state forever.
+pub(crate) fn find_conflict<TS, S, A, C, DC>(
+    transition_system: &TS,
+    solutions: A2<(usize, &Solution<Arc<SippState<S, C, DC>>, A, C, DC>)>,
+) -> Option<A2<Move<S, A, C, DC>>>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + Eq + Clone,
+    A: Copy,
+    C: Ord + LimitValues + Sub<C, Output = DC> + Copy + Default,
+    DC: Copy,
+{
+    let agents = T2(solutions.0 .0, solutions.1 .0);
+    let solutions = T2(solutions.0 .1, solutions.1 .1);
+
+    // Iterate through both solutions and find moves overlapping in C
+    let mut index = T2(0, 0);
+    let mut intervals = T2(Interval::default(), Interval::default());
+    loop {
+        // Compute the interval of each move
+        for k in 0..=1 {
+            intervals[k].start = solutions[k].steps[index[k]].1;
+            intervals[k].end = if index[k] < solutions[k].actions.len() {
+                solutions[k].steps[index[k] + 1].1
+            } else {
+                C::max_value()
+            };
+        }
+
+        // Ignore moves with no duration
+        if intervals[0].start == intervals[0].end {
+            index[0] += 1;
+            continue;
+        } else if intervals[1].start == intervals[1].end {
+            index[1] += 1;
+            continue;
+        }
+
+        // Check if the intervals overlap
+        if intervals[0].overlaps(&intervals[1]) {
+            // Check if the moves lead to a conflict
+            let get_move = |k: usize| {
+                let from = &solutions[k].steps[index[k]].0.internal_state;
+                Move::new(
+                    agents[k],
+                    from.clone(),
+                    solutions[k]
+                        .steps
+                        .get(index[k] + 1)
+                        .map(|s| s.0.internal_state.clone())
+                        .unwrap_or(from.clone()),
+                    solutions[k].actions.get(index[k]).and_then(|a| a.action),
+                    intervals[k],
+                )
+            };
+            let moves = T2(get_move(0), get_move(1));
+
+            if transition_system.conflict(T2(&moves.0, &moves.1)) {
+                return Some(moves);
+            }
+        }
+
+        if index[0] < solutions[0].actions.len() && intervals[0].end <= intervals[1].end {
+            index[0] += 1;
+        } else if index[1] < solutions[1].actions.len() {
+            index[1] += 1;
+        } else {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, Action, Graph, GraphEdgeId, GraphNodeId, Interval, MyTime, SimpleNodeData,
+        SimpleState, SimpleWorld, SippState, Solution, Task, TransitionSystem,
+    };
+
+    use super::{validate_solutions, Violation};
+
+    /// Builds a solution visiting the given nodes at the given times, using the edges between them if any.
+    fn get_solution(
+        transition_system: &SimpleWorld,
+        steps: &[(usize, f64)],
+    ) -> Solution<Arc<SippState<SimpleState, MyTime, MyTime>>, GraphEdgeId, MyTime, MyTime> {
+        let mut solution = Solution::default();
+        for (i, (node, time)) in steps.iter().enumerate() {
+            let state = SimpleState(GraphNodeId(*node));
+            if i > 0 {
+                let (previous, previous_time) = steps[i - 1];
+                let previous = SimpleState(GraphNodeId(previous));
+                let cost = OrderedFloat(time - previous_time);
+                solution.actions.push(if previous == state {
+                    Action::wait(cost)
+                } else {
+                    let edge = transition_system
+                        .actions_from(&previous)
+                        .find(|edge| transition_system.transition(&previous, edge) == state)
+                        .copied()
+                        .unwrap_or(GraphEdgeId(usize::MAX));
+                    Action::new(edge, cost)
+                });
+            }
+            solution.steps.push((
+                Arc::new(SippState {
+                    safe_interval: Interval::default(),
+                    internal_state: state,
+                }),
+                OrderedFloat(*time),
+            ));
+        }
+        solution.cost = OrderedFloat(steps[steps.len() - 1].1);
+        solution
+    }
+
+    fn get_task(from: usize, to: usize) -> Arc<Task<SimpleState, MyTime>> {
+        Arc::new(Task::new(
+            SimpleState(GraphNodeId(from)),
+            SimpleState(GraphNodeId(to)),
+            OrderedFloat(0.0),
+        ))
+    }
+
+    #[test]
+    fn test_validate() {
+        let transition_system = SimpleWorld::new(simple_graph(3), 0.4);
+        let precision = OrderedFloat(1e-6);
+        let validate = |tasks: &[_], steps: &[&[(usize, f64)]]| {
+            let solutions = steps
+                .iter()
+                .map(|steps| get_solution(&transition_system, steps))
+                .collect::<Vec<_>>();
+            validate_solutions(&transition_system, tasks, &solutions, precision)
+        };
+
+        let tasks = [get_task(0, 2), get_task(3, 4)];
+        let path = [(0, 0.0), (1, 1.0), (2, 2.0)];
+
+        // Parallel paths
+        assert!(validate(&tasks, &[&path, &[(3, 0.0), (4, 1.0)]]).is_empty());
+
+        // Head-on collision
+        let violations = validate(
+            &[get_task(0, 2), get_task(2, 0)],
+            &[&path, &[(2, 0.0), (1, 1.0), (0, 2.0)]],
+        );
+        assert!(matches!(violations[..], [Violation::Conflict { .. }]));
+
+        // Wrong start and goal
+        let violations = validate(&tasks[..1], &[&[(1, 0.0), (0, 1.0)]]);
+        assert!(matches!(
+            violations[..],
+            [
+                Violation::InvalidStart { agent: 0, .. },
+                Violation::InvalidGoal { agent: 0, .. }
+            ]
+        ));
+
+        // Too fast, and missing edge
+        let violations = validate(&tasks[..1], &[&[(0, 0.0), (1, 0.5), (2, 1.5)]]);
+        assert!(matches!(
+            violations[..],
+            [Violation::InvalidDuration { step: 0, .. }]
+        ));
+        let violations = validate(&tasks[..1], &[&[(0, 0.0), (2, 2.0)]]);
+        assert!(matches!(
+            violations[..],
+            [Violation::InvalidAction { step: 0, .. }]
+        ));
+
+        // Missing solution
+        let violations = validate(&tasks, &[&path]);
+        assert!(matches!(
+            violations[..],
+            [Violation::MalformedSolution { agent: 1 }]
+        ));
+    }
+
+    #[test]
+    fn test_validate_waits() {
+        let mut graph = Graph::new();
+        let start = graph.add_node(SimpleNodeData {
+            no_wait: true,
+            ..(0.0, 0.0).into()
+        });
+        let goal = graph.add_node(SimpleNodeData {
+            max_dwell: Some(1.0),
+            ..(1.0, 0.0).into()
+        });
+        graph.add_edge(start, goal, 1.0.into());
+        graph.add_edge(goal, start, 1.0.into());
+        let transition_system = SimpleWorld::new(Arc::new(graph), 0.4);
+
+        let tasks = [get_task(0, 0)];
+        let validate = |steps: &[(usize, f64)]| {
+            let solutions = [get_solution(&transition_system, steps)];
+            validate_solutions(&transition_system, &tasks, &solutions, OrderedFloat(1e-6))
+        };
+
+        assert!(validate(&[(0, 0.0), (1, 1.0), (1, 1.5), (1, 2.0), (0, 3.0)]).is_empty());
+
+        let violations = validate(&[(0, 0.0), (0, 1.0), (1, 2.0), (1, 3.0), (1, 3.5), (0, 4.5)]);
+        assert!(matches!(
+            violations[..],
+            [
+                Violation::ForbiddenWait { step: 0, .. },
+                Violation::WaitTooLong { step: 3, .. }
+            ]
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{get_cbs_from_files, MapfParseError};
+
+/// A benchmark instance, given by a map file and a task file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkInstance {
+    /// The name of the instance, given by the path of the task file relative to the searched directory.
+    pub name: String,
+    /// The file describing the map.
+    pub map_file: PathBuf,
+    /// The file describing the tasks.
+    pub task_file: PathBuf,
+}
+
+/// Returns all benchmark instances found in the given directory and its subdirectories.
+/// A directory contains instances either if it has a `map.xml` file, in which case all other XML files
+/// are task files, or if it has a single MovingAI `.map` file, in which case all `.scen` files are task files.
+pub fn find_instances(directory: &Path) -> io::Result<Vec<BenchmarkInstance>> {
+    let mut instances = vec![];
+    find_instances_rec(directory, directory, &mut instances)?;
+    // Sort the task files of each map in natural order (e.g. task-2 before task-10)
+    instances.sort_by(|a, b| {
+        (&a.map_file, a.name.len(), &a.name).cmp(&(&b.map_file, b.name.len(), &b.name))
+    });
+    Ok(instances)
+}
+
+fn find_instances_rec(
+    root: &Path,
+    directory: &Path,
+    instances: &mut Vec<BenchmarkInstance>,
+) -> io::Result<()> {
+    let mut files = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_instances_rec(root, &path, instances)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    let has_extension =
+        |path: &PathBuf, extension: &str| path.extension().is_some_and(|e| e == extension);
+
+    let xml_map = directory.join("map.xml");
+    let movingai_maps = files
+        .iter()
+        .filter(|path| has_extension(path, "map"))
+        .collect::<Vec<_>>();
+    let (map_file, task_files) = if files.contains(&xml_map) {
+        let task_files = files
+            .iter()
+            .filter(|path| has_extension(path, "xml") && **path != xml_map)
+            .collect::<Vec<_>>();
+        (xml_map.clone(), task_files)
+    } else if let [map_file] = movingai_maps[..] {
+        let task_files = files
+            .iter()
+            .filter(|path| has_extension(path, "scen"))
+            .collect::<Vec<_>>();
+        (map_file.clone(), task_files)
+    } else {
+        return Ok(());
+    };
+
+    for task_file in task_files {
+        instances.push(BenchmarkInstance {
+            name: task_file
+                .strip_prefix(root)
+                .unwrap_or(task_file)
+                .to_string_lossy()
+                .to_string(),
+            map_file: map_file.clone(),
+            task_file: task_file.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+/// The result of a benchmark run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    /// The name of the instance.
+    pub instance: String,
+    /// The number of agents.
+    pub n_agents: usize,
+    /// The number of threads used.
+    pub n_threads: usize,
+    /// Whether a solution was found within the time limit.
+    pub success: bool,
+    /// The sum of the costs of the solution, if any.
+    pub sum_of_costs: Option<f64>,
+    /// The makespan of the solution, if any.
+    pub makespan: Option<f64>,
+    /// The number of CBS nodes expanded.
+    pub expanded: usize,
+    /// The number of searches performed by SIPP.
+    pub sipp_searches: usize,
+    /// The number of search nodes expanded by SIPP.
+    pub sipp_expanded: usize,
+    /// The number of new queries to RRA*.
+    pub rra_new_queries: usize,
+    /// The number of cached queries to RRA*.
+    pub rra_cached_queries: usize,
+    /// The number of search nodes expanded by RRA*.
+    pub rra_expanded: usize,
+    /// The wall time of the run, including the construction of the problem.
+    pub wall_time: Duration,
+}
+
+impl BenchmarkResult {
+    /// The header of the CSV file, matching [`BenchmarkResult::to_csv_record`].
+    pub const CSV_HEADER: &'static str = "instance,agents,threads,success,soc,makespan,expanded,\
+        sipp_searches,sipp_expanded,rra_new_queries,rra_cached_queries,rra_expanded,wall_time";
+
+    /// Returns the result as a CSV record, without a trailing newline.
+    pub fn to_csv_record(&self) -> String {
+        let optional = |value: Option<f64>| value.map_or(String::new(), |v| v.to_string());
+        format!(
+            "\"{}\",{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.instance.replace('"', "\"\""),
+            self.n_agents,
+            self.n_threads,
+            self.success,
+            optional(self.sum_of_costs),
+            optional(self.makespan),
+            self.expanded,
+            self.sipp_searches,
+            self.sipp_expanded,
+            self.rra_new_queries,
+            self.rra_cached_queries,
+            self.rra_expanded,
+            self.wall_time.as_secs_f64(),
+        )
+    }
+}
+
+/// Solves the given instance with the given number of agents, and returns the result,
+/// or None if the instance has fewer agents.
+///
+/// # Arguments
+///
+/// * `instance` - The instance to solve
+/// * `config_file` - The configuration file
+/// * `n_agents` - The number of agents to consider
+/// * `n_threads` - The number of threads to use
+/// * `time_limit` - The time limit for the search, or None to use the one of the configuration
+pub fn run_benchmark(
+    instance: &BenchmarkInstance,
+    config_file: &str,
+    n_agents: usize,
+    n_threads: usize,
+    time_limit: Option<Duration>,
+) -> Result<Option<BenchmarkResult>, MapfParseError> {
+    let start = Instant::now();
+
+    let (_, mut cbs, mut config, _) = get_cbs_from_files(
+        &instance.map_file.to_string_lossy(),
+        &instance.task_file.to_string_lossy(),
+        config_file,
+        n_agents,
+        n_threads,
+    )?;
+    if config.n_agents < n_agents {
+        return Ok(None);
+    }
+    if time_limit.is_some() {
+        config.time_limit = time_limit;
+    }
+
+    let solutions = cbs.solve(&config);
+    let stats = cbs.get_stats();
+    let costs = solutions.map(|solutions| {
+        solutions
+            .iter()
+            .map(|solution| solution.cost.0)
+            .collect::<Vec<_>>()
+    });
+
+    Ok(Some(BenchmarkResult {
+        instance: instance.name.clone(),
+        n_agents,
+        n_threads,
+        success: costs.is_some(),
+        sum_of_costs: costs.as_ref().map(|costs| costs.iter().sum()),
+        makespan: costs
+            .as_ref()
+            .map(|costs| costs.iter().copied().fold(0.0, f64::max)),
+        expanded: stats.expanded,
+        sipp_searches: stats.lsipp_stats.sipp_stats.searches,
+        sipp_expanded: stats.lsipp_stats.sipp_stats.expanded,
+        rra_new_queries: stats.rra_stats.new_query,
+        rra_cached_queries: stats.rra_stats.cached_query,
+        rra_expanded: stats.rra_stats.expanded,
+        wall_time: start.elapsed(),
+    }))
+}
+
+/// Solves the given instance with an increasing number of agents, until the search fails
+/// or all agents of the instance are considered, and calls the given callback with each result.
+///
+/// # Arguments
+///
+/// * `instance` - The instance to solve
+/// * `config_file` - The configuration file
+/// * `agents` - The numbers of agents to consider, in increasing order
+/// * `n_threads` - The number of threads to use
+/// * `time_limit` - The time limit for each search, or None to use the one of the configuration
+/// * `callback` - The function to call with each result
+pub fn sweep_agents<I, F>(
+    instance: &BenchmarkInstance,
+    config_file: &str,
+    agents: I,
+    n_threads: usize,
+    time_limit: Option<Duration>,
+    mut callback: F,
+) -> Result<(), MapfParseError>
+where
+    I: IntoIterator<Item = usize>,
+    F: FnMut(BenchmarkResult),
+{
+    for n_agents in agents {
+        match run_benchmark(instance, config_file, n_agents, n_threads, time_limit)? {
+            Some(result) => {
+                let success = result.success;
+                callback(result);
+                if !success {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{find_instances, sweep_agents, BenchmarkResult};
+
+    #[test]
+    fn test_sweep() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/instances");
+        let instances = find_instances(&directory).unwrap();
+
+        let instance = instances
+            .iter()
+            .find(|instance| instance.name == "empty-16-16-random/empty-16-16-random-1.xml")
+            .unwrap();
+        assert!(instance.map_file.ends_with("empty-16-16-random/map.xml"));
+        assert!(!instances
+            .iter()
+            .any(|instance| instance.task_file.ends_with("map.xml")));
+
+        let config_file =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/config/config-2.xml");
+        let mut results = vec![];
+        sweep_agents(
+            instance,
+            config_file.to_str().unwrap(),
+            [1, 2, 3],
+            1,
+            None,
+            |result| results.push(result),
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.success));
+        assert_eq!(results[0].sum_of_costs, results[0].makespan);
+        assert_eq!(
+            BenchmarkResult::CSV_HEADER.split(',').count(),
+            results[0].to_csv_record().split(',').count()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::{fs, io};
+
+use quick_xml::{se::to_string, DeError};
+use serde::Serialize;
+
+use crate::{CbsStats, MyTime, Solution};
+
+/// Returns the given solutions and search statistics in the `_log.xml` format of
+/// Continuous-CBS, from <https://github.com/PathPlanning/Continuous-CBS>, for example:
+/// ```xml
+/// <?xml version="1.0" ?>
+/// <root>
+/// <log>
+///     <summary time="0.01" flowtime="4" makespan="2" HLexpansions="1" LLexpansions="6"/>
+///     <agent number="0">
+///         <path duration="2">
+///             <section id="0" start_i="0" start_j="0" goal_i="1" goal_j="0" duration="1"/>
+///             <section id="1" start_i="1" start_j="0" goal_i="2" goal_j="0" duration="1"/>
+///         </path>
+///     </agent>
+/// </log>
+/// </root>
+/// ```
+/// The `i` and `j` coordinates of a section correspond to the `y` and `x` coordinates of its states,
+/// as for the cells of the grid maps, and waiting is described by a section between identical states.
+///
+/// # Arguments
+///
+/// * `solutions` - The solution of each agent
+/// * `stats` - The statistics of the search that found the solutions
+/// * `coordinates` - A function that returns the (x,y) coordinates of a state
+pub fn to_ccbs_log<S, A, F>(
+    solutions: &[Solution<S, A, MyTime, MyTime>],
+    stats: &CbsStats,
+    coordinates: F,
+) -> Result<String, DeError>
+where
+    F: Fn(&S) -> (f64, f64),
+{
+    let agents = solutions
+        .iter()
+        .enumerate()
+        .map(|(number, solution)| AgentLog {
+            number,
+            path: PathLog {
+                duration: solution.cost.0,
+                sections: solution
+                    .steps
+                    .windows(2)
+                    .enumerate()
+                    .map(|(id, steps)| {
+                        let (start_j, start_i) = coordinates(&steps[0].0);
+                        let (goal_j, goal_i) = coordinates(&steps[1].0);
+                        Section {
+                            id,
+                            start_i,
+                            start_j,
+                            goal_i,
+                            goal_j,
+                            duration: (steps[1].1 - steps[0].1).0,
+                        }
+                    })
+                    .collect(),
+            },
+        })
+        .collect();
+
+    let log = LogRoot {
+        log: Log {
+            summary: Summary {
+                time: stats.elapsed.as_secs_f64(),
+                flowtime: solutions.iter().map(|solution| solution.cost.0).sum(),
+                makespan: solutions
+                    .iter()
+                    .map(|solution| solution.cost.0)
+                    .fold(0.0, f64::max),
+                hl_expansions: stats.expanded,
+                ll_expansions: stats.lsipp_stats.sipp_stats.expanded,
+            },
+            agents,
+        },
+    };
+
+    Ok(format!("<?xml version=\"1.0\" ?>\n{}\n", to_string(&log)?))
+}
+
+/// Writes the given solutions and search statistics to a file, in the `_log.xml` format of
+/// Continuous-CBS (see [`to_ccbs_log`]).
+///
+/// # Arguments
+///
+/// * `filename` - The file to write
+/// * `solutions` - The solution of each agent
+/// * `stats` - The statistics of the search that found the solutions
+/// * `coordinates` - A function that returns the (x,y) coordinates of a state
+pub fn write_ccbs_log<S, A, F>(
+    filename: &str,
+    solutions: &[Solution<S, A, MyTime, MyTime>],
+    stats: &CbsStats,
+    coordinates: F,
+) -> io::Result<()>
+where
+    F: Fn(&S) -> (f64, f64),
+{
+    let contents = to_ccbs_log(solutions, stats, coordinates).map_err(io::Error::other)?;
+    fs::write(filename, contents)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename = "root")]
+struct LogRoot {
+    log: Log,
+}
+
+#[derive(Debug, Serialize)]
+struct Log {
+    summary: Summary,
+    #[serde(rename = "agent")]
+    agents: Vec<AgentLog>,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    #[serde(rename = "@time")]
+    time: f64,
+    #[serde(rename = "@flowtime")]
+    flowtime: f64,
+    #[serde(rename = "@makespan")]
+    makespan: f64,
+    #[serde(rename = "@HLexpansions")]
+    hl_expansions: usize,
+    #[serde(rename = "@LLexpansions")]
+    ll_expansions: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct AgentLog {
+    #[serde(rename = "@number")]
+    number: usize,
+    path: PathLog,
+}
+
+#[derive(Debug, Serialize)]
+struct PathLog {
+    #[serde(rename = "@duration")]
+    duration: f64,
+    #[serde(rename = "section")]
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Serialize)]
+struct Section {
+    #[serde(rename = "@id")]
+    id: usize,
+    #[serde(rename = "@start_i")]
+    start_i: f64,
+    #[serde(rename = "@start_j")]
+    start_j: f64,
+    #[serde(rename = "@goal_i")]
+    goal_i: f64,
+    #[serde(rename = "@goal_j")]
+    goal_j: f64,
+    #[serde(rename = "@duration")]
+    duration: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, CbsConfig, ConflictBasedSearch, GraphEdgeId, GraphNodeId, MyTime,
+        SimpleHeuristic, SimpleState, SimpleWorld, Task,
+    };
+
+    use super::to_ccbs_log;
+
+    #[test]
+    fn test_log() {
+        let size = 3;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(SimpleWorld::new(graph.clone(), 0.4));
+        let tasks = vec![
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(2)),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(3)),
+                SimpleState(GraphNodeId(3)),
+                OrderedFloat(0.0),
+            )),
+        ];
+        let config: CbsConfig<_, _, GraphEdgeId, MyTime, MyTime, SimpleHeuristic> = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+        let mut solver = ConflictBasedSearch::new(transition_system);
+        let solutions = solver.solve(&config).unwrap();
+        let stats = solver.get_stats();
+
+        let log = to_ccbs_log(&solutions, &stats, |state| {
+            let node = graph.get_node(state.internal_state.0);
+            (node.data.x, node.data.y)
+        })
+        .unwrap();
+
+        assert!(log.starts_with("<?xml version=\"1.0\" ?>\n<root><log><summary time="));
+        assert!(log.contains("flowtime=\"2\" makespan=\"2\""));
+        assert!(log.contains(
+            "<agent number=\"0\"><path duration=\"2\">\
+            <section id=\"0\" start_i=\"0\" start_j=\"0\" goal_i=\"1\" goal_j=\"0\" duration=\"1\"/>\
+            <section id=\"1\" start_i=\"1\" start_j=\"0\" goal_i=\"2\" goal_j=\"0\" duration=\"1\"/>\
+            </path></agent>"
+        ));
+        assert!(log.contains("<agent number=\"1\"><path duration=\"0\"/></agent>"));
+    }
+}
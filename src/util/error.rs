@@ -0,0 +1,105 @@
+use std::{error::Error, fmt::Display, io};
+
+/// An error encountered while parsing the input files of a problem.
+#[derive(Debug)]
+pub enum MapfParseError {
+    /// The file could not be read.
+    Io {
+        /// The file that could not be read.
+        file: String,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The file does not follow the expected format.
+    Syntax {
+        /// The malformed file.
+        file: String,
+        /// The reason why the file could not be parsed.
+        reason: String,
+    },
+    /// An element of the file has an invalid or missing value.
+    Invalid {
+        /// The file containing the element.
+        file: String,
+        /// The invalid element.
+        element: String,
+        /// The reason why the element is invalid.
+        reason: String,
+    },
+    /// An element of the file refers to a node that does not exist.
+    UnknownNode {
+        /// The file containing the element.
+        file: String,
+        /// The element referring to the node.
+        element: String,
+        /// The identifier of the unknown node.
+        node: String,
+    },
+    /// A start or goal cell is blocked by an obstacle or outside of the map.
+    BlockedCell {
+        /// The file containing the element.
+        file: String,
+        /// The element referring to the cell.
+        element: String,
+        /// The coordinates of the cell.
+        cell: (usize, usize),
+    },
+}
+
+impl MapfParseError {
+    /// Returns the file in which the error was encountered.
+    pub fn file(&self) -> &str {
+        match self {
+            Self::Io { file, .. }
+            | Self::Syntax { file, .. }
+            | Self::Invalid { file, .. }
+            | Self::UnknownNode { file, .. }
+            | Self::BlockedCell { file, .. } => file,
+        }
+    }
+
+    pub(crate) fn invalid(file: &str, element: impl Display, reason: impl Display) -> Self {
+        Self::Invalid {
+            file: file.to_string(),
+            element: element.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl Display for MapfParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { file, source } => write!(f, "{}: cannot read file: {}", file, source),
+            Self::Syntax { file, reason } => write!(f, "{}: {}", file, reason),
+            Self::Invalid {
+                file,
+                element,
+                reason,
+            } => write!(f, "{}: invalid {}: {}", file, element, reason),
+            Self::UnknownNode {
+                file,
+                element,
+                node,
+            } => write!(f, "{}: {} refers to unknown node {}", file, element, node),
+            Self::BlockedCell {
+                file,
+                element,
+                cell,
+            } => write!(
+                f,
+                "{}: {} refers to cell ({}, {}), which is blocked or outside of the map",
+                file, element, cell.0, cell.1
+            ),
+        }
+    }
+}
+
+impl Error for MapfParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
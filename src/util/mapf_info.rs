@@ -1,31 +1,118 @@
-use std::{collections::HashMap, error::Error, fs::File, io::Read, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap, fmt::Display, fs::File, io::Read, str::FromStr, sync::Arc, time::Duration,
+};
 
+use ncollide2d::{
+    na::{Isometry2, Point2, Vector2},
+    query,
+    shape::{Cuboid, Segment},
+};
 use ordered_float::OrderedFloat;
 use quick_xml::{de::from_str, DeError};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 
 use crate::{
-    CbsConfig, ConflictBasedSearch, Graph, GraphEdgeId, GraphNodeId, MyTime, SimpleEdgeData,
-    SimpleHeuristic, SimpleNodeData, SimpleState, SimpleWorld, Task,
+    parse_movingai_inputs, CbsConfig, ConflictBasedSearch, Graph, GraphEdgeId, GraphNodeId,
+    HeadingAction, HeadingHeuristic, HeadingState, HeadingWorld, MapfParseError, MyTime,
+    SimpleEdgeData, SimpleHeuristic, SimpleNodeData, SimpleState, SimpleWorld, Task,
 };
 
 /// Builds a CBS algorithm and its configuration from the given files.
+/// Returns an error if the files cannot be parsed, see [`parse_inputs`].
 pub fn get_cbs_from_files(
     map_file: &str,
     task_file: &str,
     config_file: &str,
     n_agents: usize,
     n_threads: usize,
-) -> (
-    Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
-    ConflictBasedSearch<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime, SimpleHeuristic>,
-    CbsConfig<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime, SimpleHeuristic>,
-    f64,
-) {
-    let (graph, tasks, config) = parse_inputs(map_file, task_file, config_file, n_agents).unwrap();
+) -> Result<
+    (
+        Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        ConflictBasedSearch<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime, SimpleHeuristic>,
+        CbsConfig<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime, SimpleHeuristic>,
+        f64,
+    ),
+    MapfParseError,
+> {
+    let (graph, tasks, config) = parse_inputs(map_file, task_file, config_file, n_agents)?;
     let transition_system = Arc::new(SimpleWorld::new(graph.clone(), config.agent_size));
 
+    Ok((
+        graph,
+        ConflictBasedSearch::new(transition_system.clone()),
+        CbsConfig::new(
+            transition_system,
+            tasks,
+            OrderedFloat(config.precision),
+            n_threads,
+            Some(Duration::from_secs_f64(config.time_limit)),
+        ),
+        config.agent_size,
+    ))
+}
+
+/// Builds a CBS algorithm and its configuration from the given files, in a world where
+/// agents must rotate in place to face the edges they move along.
+/// Agents start with the heading that is the closest to the x axis, and finish with
+/// the heading that is the closest to the given goal heading.
+/// Returns an error if the files cannot be parsed, see [`parse_inputs`],
+/// or if the angular speed is not positive.
+///
+/// # Arguments
+///
+/// * `angular_speed` - The angular speed of the agents, in radians per unit of time.
+/// * `goal_heading` - The desired heading of the agents at their goal, in radians.
+pub fn get_heading_cbs_from_files(
+    map_file: &str,
+    task_file: &str,
+    config_file: &str,
+    n_agents: usize,
+    n_threads: usize,
+    angular_speed: f64,
+    goal_heading: f64,
+) -> Result<
     (
+        Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        ConflictBasedSearch<
+            HeadingWorld,
+            HeadingState,
+            HeadingAction,
+            MyTime,
+            MyTime,
+            HeadingHeuristic,
+        >,
+        CbsConfig<HeadingWorld, HeadingState, HeadingAction, MyTime, MyTime, HeadingHeuristic>,
+        f64,
+    ),
+    MapfParseError,
+> {
+    if angular_speed.is_nan() || angular_speed <= 0.0 {
+        return Err(MapfParseError::invalid(
+            config_file,
+            "angular_speed",
+            format!("{} is not a positive speed", angular_speed),
+        ));
+    }
+
+    let (graph, tasks, config) = parse_inputs(map_file, task_file, config_file, n_agents)?;
+    let transition_system = Arc::new(HeadingWorld::new(
+        graph.clone(),
+        config.agent_size,
+        angular_speed,
+    ));
+
+    let tasks = tasks
+        .iter()
+        .map(|task| {
+            Arc::new(Task::new(
+                transition_system.get_state(task.initial_state.0, 0.0),
+                transition_system.get_state(task.goal_state.0, goal_heading),
+                task.initial_cost,
+            ))
+        })
+        .collect();
+
+    Ok((
         graph,
         ConflictBasedSearch::new(transition_system.clone()),
         CbsConfig::new(
@@ -36,18 +123,50 @@ pub fn get_cbs_from_files(
             Some(Duration::from_secs_f64(config.time_limit)),
         ),
         config.agent_size,
-    )
+    ))
 }
 
-/// Parse the benchmark maps and scenarios from <https://movingai.com/benchmarks/mapf/index.html>
-pub fn read_from_file(filename: &str) -> Result<String, Box<dyn Error>> {
-    let mut file = File::open(filename)?;
+/// Reads the contents of the given file.
+pub fn read_from_file(filename: &str) -> Result<String, MapfParseError> {
     let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    File::open(filename)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|source| MapfParseError::Io {
+            file: filename.to_string(),
+            source,
+        })?;
     Ok(contents)
 }
 
-/// Parse the benchmark maps and scenarios from <https://movingai.com/benchmarks/mapf/index.html>
+/// Deserializes the contents of the given XML file.
+fn read_xml<T>(filename: &str) -> Result<T, MapfParseError>
+where
+    T: DeserializeOwned,
+{
+    let contents = read_from_file(filename)?;
+    let data: Result<T, DeError> = from_str(&contents);
+    data.map_err(|e| MapfParseError::Syntax {
+        file: filename.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Parses the value of the given element of a file.
+fn parse_value<T>(file: &str, element: impl Display, value: &str) -> Result<T, MapfParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| MapfParseError::invalid(file, element, format!("{:?}: {}", value, e)))
+}
+
+/// Parse the benchmark maps and scenarios from <https://movingai.com/benchmarks/mapf/index.html>,
+/// either in the XML format, or in the MovingAI format if the map file has the `.map` extension.
+/// Returns an error if a file is malformed, or if an agent starts or ends on an obstacle
+/// or on a node that does not exist.
 pub fn parse_inputs(
     map_file: &str,
     task_file: &str,
@@ -59,15 +178,14 @@ pub fn parse_inputs(
         Vec<Arc<Task<SimpleState, MyTime>>>,
         Config,
     ),
-    Box<dyn Error>,
+    MapfParseError,
 > {
-    let contents = read_from_file(map_file)?;
-    let data: Result<Map, DeError> = from_str(&contents);
-    let map = data?;
+    if map_file.ends_with(".map") {
+        return parse_movingai_inputs(map_file, task_file, config_file, None, n_agents);
+    }
 
-    let contents = read_from_file(task_file)?;
-    let data: Result<Scenario, DeError> = from_str(&contents);
-    let mut scenario = data?;
+    let map: Map = read_xml(map_file)?;
+    let mut scenario: Scenario = read_xml(task_file)?;
     scenario.agents.truncate(n_agents);
 
     let config = parse_config(config_file)?;
@@ -76,81 +194,286 @@ pub fn parse_inputs(
     let mut tasks = Vec::new();
 
     if let Some(map) = map.grid {
-        let mut grid = vec![vec![GraphNodeId(0); map.width]; map.height];
-        for x in 0..map.width {
-            for y in 0..map.height {
-                if map.grid.rows[y][x] == 1 {
-                    // 1 is an obstacle
-                    continue;
-                }
-                grid[y][x] = graph.add_node((x as f64, y as f64));
-            }
+        let rows = &map.grid.rows;
+        if rows.len() != map.height || rows.iter().any(|row| row.len() != map.width) {
+            return Err(MapfParseError::invalid(
+                map_file,
+                "grid",
+                format!("expected {} rows of {} cells", map.height, map.width),
+            ));
         }
 
-        // TODO: use connectedness parameter
-        for x in 0..map.width {
-            for y in 0..map.height {
-                if map.grid.rows[y][x] == 1 {
-                    // 1 is an obstacle
-                    continue;
-                }
-                let node_id = grid[y][x];
-                if x > 0 && map.grid.rows[y][x - 1] == 0 {
-                    graph.add_edge(node_id, grid[y][x - 1], 1.0);
-                }
-                if y > 0 && map.grid.rows[y - 1][x] == 0 {
-                    graph.add_edge(node_id, grid[y - 1][x], 1.0);
-                }
-                if x < map.width - 1 && map.grid.rows[y][x + 1] == 0 {
-                    graph.add_edge(node_id, grid[y][x + 1], 1.0);
-                }
-                if y < map.height - 1 && map.grid.rows[y + 1][x] == 0 {
-                    graph.add_edge(node_id, grid[y + 1][x], 1.0);
+        let grid = build_grid_graph(
+            &mut graph,
+            map.width,
+            map.height,
+            rows,
+            config.connectedness,
+            config.agent_size,
+        )
+        .ok_or_else(|| {
+            MapfParseError::invalid(config_file, "connectedness", config.connectedness)
+        })?;
+
+        let cell = |agent: usize, kind: &str, i: Option<usize>, j: Option<usize>| {
+            let element = format!("{} of agent {}", kind, agent);
+            match (i, j) {
+                (Some(i), Some(j)) if i < map.height && j < map.width && rows[i][j] == 0 => {
+                    Ok(SimpleState(grid[i][j]))
                 }
+                (Some(i), Some(j)) => Err(MapfParseError::BlockedCell {
+                    file: task_file.to_string(),
+                    element,
+                    cell: (i, j),
+                }),
+                _ => Err(MapfParseError::invalid(
+                    task_file,
+                    element,
+                    "missing cell coordinates",
+                )),
             }
-        }
+        };
 
-        for agent in scenario.agents {
-            let initial_state = SimpleState(grid[agent.start_i.unwrap()][agent.start_j.unwrap()]);
-            let goal_state = SimpleState(grid[agent.goal_i.unwrap()][agent.goal_j.unwrap()]);
+        for (i, agent) in scenario.agents.iter().enumerate() {
             tasks.push(Arc::new(Task::new(
-                initial_state,
-                goal_state,
+                cell(i, "start", agent.start_i, agent.start_j)?,
+                cell(i, "goal", agent.goal_i, agent.goal_j)?,
                 OrderedFloat(0.0),
             )));
         }
-    } else if let Some(map) = map.graph {
+    } else if let Some(graph_map) = map.graph {
+        // Names of the attributes, indexed by key
+        let keys = map
+            .keys
+            .iter()
+            .map(|key| (key.id.as_str(), key.name.as_str()))
+            .collect::<HashMap<_, _>>();
+
         let mut nodes = HashMap::new();
-        for node in map.nodes {
-            let position = node
-                .position
-                .split(',')
-                .map(|n| n.parse().unwrap())
-                .collect::<Vec<f64>>();
-            nodes.insert(node.id, graph.add_node((position[0], position[1])));
+        for node in graph_map.nodes {
+            let element = format!("node {}", node.id);
+            let mut position = None;
+            let mut no_wait = false;
+            let mut max_dwell = None;
+            for data in node.data {
+                match keys.get(data.key.as_str()).copied().unwrap_or("coords") {
+                    "coords" => {
+                        let coords = data
+                            .value
+                            .split(',')
+                            .map(|n| parse_value(map_file, &element, n))
+                            .collect::<Result<Vec<f64>, _>>()?;
+                        if coords.len() != 2 {
+                            return Err(MapfParseError::invalid(
+                                map_file,
+                                &element,
+                                format!("expected 2 coordinates, found {:?}", data.value),
+                            ));
+                        }
+                        position = Some((coords[0], coords[1]));
+                    }
+                    "no_wait" => no_wait = parse_value(map_file, &element, &data.value)?,
+                    "max_dwell" => max_dwell = Some(parse_value(map_file, &element, &data.value)?),
+                    _ => {}
+                }
+            }
+            let (x, y) = position.ok_or_else(|| {
+                MapfParseError::invalid(map_file, &element, "missing coordinates")
+            })?;
+            nodes.insert(
+                node.id,
+                graph.add_node(SimpleNodeData {
+                    x,
+                    y,
+                    no_wait,
+                    max_dwell,
+                }),
+            );
         }
-        for edge in map.edges {
-            graph.add_edge(nodes[&edge.source], nodes[&edge.target], edge.weight);
+
+        let get_node = |file: &str, element: &str, id: &str| {
+            nodes
+                .get(id)
+                .copied()
+                .ok_or_else(|| MapfParseError::UnknownNode {
+                    file: file.to_string(),
+                    element: element.to_string(),
+                    node: id.to_string(),
+                })
+        };
+
+        for edge in graph_map.edges {
+            let element = format!("edge from {} to {}", edge.source, edge.target);
+            let mut edge_data = SimpleEdgeData::default();
+            for data in edge.data {
+                match keys.get(data.key.as_str()).copied().unwrap_or("weight") {
+                    "weight" => edge_data.weight = parse_value(map_file, &element, &data.value)?,
+                    "speed_limit" => {
+                        edge_data.speed_limit = Some(parse_value(map_file, &element, &data.value)?)
+                    }
+                    "duration" => {
+                        edge_data.duration = Some(parse_value(map_file, &element, &data.value)?)
+                    }
+                    _ => {}
+                }
+            }
+            let positive = |value: f64| value > 0.0;
+            if !positive(edge_data.weight)
+                || !edge_data.speed_limit.is_none_or(positive)
+                || !edge_data.duration.is_none_or(positive)
+            {
+                return Err(MapfParseError::invalid(
+                    map_file,
+                    &element,
+                    "weight, speed limit and duration must be positive",
+                ));
+            }
+            graph.add_edge(
+                get_node(map_file, &element, &edge.source)?,
+                get_node(map_file, &element, &edge.target)?,
+                edge_data,
+            );
         }
 
-        for agent in scenario.agents {
-            let start = nodes[&("n".to_string() + &agent.start_id.unwrap().to_string())];
-            let goal = nodes[&("n".to_string() + &agent.goal_id.unwrap().to_string())];
-            let initial_state = SimpleState(start);
-            let goal_state = SimpleState(goal);
+        let node = |agent: usize, kind: &str, id: Option<usize>| {
+            let element = format!("{} of agent {}", kind, agent);
+            let id =
+                id.ok_or_else(|| MapfParseError::invalid(task_file, &element, "missing node"))?;
+            get_node(task_file, &element, &format!("n{}", id)).map(SimpleState)
+        };
+
+        for (i, agent) in scenario.agents.iter().enumerate() {
             tasks.push(Arc::new(Task::new(
-                initial_state,
-                goal_state,
+                node(i, "start", agent.start_id)?,
+                node(i, "goal", agent.goal_id)?,
                 OrderedFloat(0.0),
             )));
         }
     } else {
-        return Err("No map found".into());
+        return Err(MapfParseError::Syntax {
+            file: map_file.to_string(),
+            reason: "no grid or graph found".to_string(),
+        });
     }
 
     Ok((Arc::new(graph), tasks, config))
 }
 
+/// Adds a node for each free cell of the given grid, and an edge for each valid move
+/// of the 2^k neighborhood, where k is the given connectedness (between 2 and 5).
+/// A move is valid if an agent of the given radius can follow it without touching obstacles.
+/// Returns the node associated with each cell, or None if the connectedness is not supported.
+pub(crate) fn build_grid_graph(
+    graph: &mut Graph<SimpleNodeData, SimpleEdgeData>,
+    width: usize,
+    height: usize,
+    rows: &[Vec<usize>],
+    connectedness: usize,
+    agent_size: f64,
+) -> Option<Vec<Vec<GraphNodeId>>> {
+    let moves: &[(isize, isize)] = match connectedness {
+        2 => &GRID_MOVES[..4],
+        3 => &GRID_MOVES[..8],
+        4 => &GRID_MOVES[..16],
+        5 => &GRID_MOVES[..32],
+        _ => return None,
+    };
+
+    let is_free = |x: isize, y: isize| {
+        x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && {
+            rows[y as usize][x as usize] == 0 // 1 is an obstacle
+        }
+    };
+
+    let mut grid = vec![vec![GraphNodeId(0); width]; height];
+    for x in 0..width {
+        for y in 0..height {
+            if is_free(x as isize, y as isize) {
+                grid[y][x] = graph.add_node((x as f64, y as f64).into());
+            }
+        }
+    }
+
+    let cell = Cuboid::new(Vector2::new(0.5, 0.5));
+    let margin = agent_size.ceil() as isize;
+    for x in 0..width as isize {
+        for y in 0..height as isize {
+            if !is_free(x, y) {
+                continue;
+            }
+            for (dx, dy) in moves {
+                let (to_x, to_y) = (x + dx, y + dy);
+                if !is_free(to_x, to_y) {
+                    continue;
+                }
+
+                // Check the distance to all obstacles around the segment
+                let segment = Segment::new(
+                    Point2::new(x as f64, y as f64),
+                    Point2::new(to_x as f64, to_y as f64),
+                );
+                let valid = (x.min(to_x) - margin..=x.max(to_x) + margin).all(|cx| {
+                    (y.min(to_y) - margin..=y.max(to_y) + margin).all(|cy| {
+                        is_free(cx, cy)
+                            || query::distance(
+                                &Isometry2::identity(),
+                                &segment,
+                                &Isometry2::translation(cx as f64, cy as f64),
+                                &cell,
+                            ) >= agent_size
+                    })
+                });
+
+                if valid {
+                    graph.add_edge(
+                        grid[y as usize][x as usize],
+                        grid[to_y as usize][to_x as usize],
+                        1.0.into(),
+                    );
+                }
+            }
+        }
+    }
+
+    Some(grid)
+}
+
+/// The moves of the 2^k neighborhoods of grid cells, sorted by increasing k.
+const GRID_MOVES: [(isize, isize); 32] = [
+    (-1, 0),
+    (0, -1),
+    (1, 0),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (1, 1),
+    (-1, 1),
+    (-2, -1),
+    (-1, -2),
+    (1, -2),
+    (2, -1),
+    (2, 1),
+    (1, 2),
+    (-1, 2),
+    (-2, 1),
+    (-3, -1),
+    (-3, -2),
+    (-2, -3),
+    (-1, -3),
+    (1, -3),
+    (2, -3),
+    (3, -2),
+    (3, -1),
+    (3, 1),
+    (3, 2),
+    (2, 3),
+    (1, 3),
+    (-1, 3),
+    (-2, 3),
+    (-3, 2),
+    (-3, 1),
+];
+
 /// A structure that corresponds to the XML format of the mapf.info benchmark maps.
 /// Either a grid map, for example:
 /// ```xml
@@ -167,7 +490,8 @@ pub fn parse_inputs(
 /// </map>
 /// </root>
 /// ```
-/// Or a graph map, for example:
+/// Or a graph map, for example (nodes may also have `no_wait` and `max_dwell` attributes,
+/// and edges may also have `speed_limit` and `duration` attributes):
 /// ```xml
 /// <?xml version="1.0" encoding="UTF-8"?>
 /// <graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
@@ -193,6 +517,8 @@ pub fn parse_inputs(
 struct Map {
     #[serde(rename = "map")]
     grid: Option<GridMap>,
+    #[serde(rename = "key", default)]
+    keys: Vec<Key>,
     graph: Option<GraphMap>,
 }
 
@@ -217,12 +543,28 @@ struct GraphMap {
     edges: Vec<Edge>,
 }
 
+#[derive(Debug, Deserialize)]
+struct Key {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(rename = "@attr.name")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    #[serde(rename = "@key")]
+    key: String,
+    #[serde(rename = "$text")]
+    value: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct Node {
     #[serde(rename = "@id")]
     id: String,
-    #[serde(rename = "data")]
-    position: String,
+    #[serde(default)]
+    data: Vec<Data>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -231,8 +573,8 @@ struct Edge {
     source: String,
     #[serde(rename = "@target")]
     target: String,
-    #[serde(rename = "data")]
-    weight: f64,
+    #[serde(default)]
+    data: Vec<Data>,
 }
 /// A structure that corresponds to the XML format of the mapf.info benchmark scenarios.
 /// Either a scenario for a grid map, for example:
@@ -274,10 +616,22 @@ struct Agent {
 }
 
 /// Parse a configuration file.
-pub fn parse_config(filename: &str) -> Result<Config, Box<dyn Error>> {
-    let contents = read_from_file(filename)?;
-    let data: Result<ConfigRoot, DeError> = from_str(&contents);
-    let config = data?.config;
+pub fn parse_config(filename: &str) -> Result<Config, MapfParseError> {
+    let config = read_xml::<ConfigRoot>(filename)?.config;
+    if !(2..=5).contains(&config.connectedness) {
+        return Err(MapfParseError::invalid(
+            filename,
+            "connectedness",
+            format!("{} is not between 2 and 5", config.connectedness),
+        ));
+    }
+    if config.agent_size.is_nan() || config.agent_size < 0.0 {
+        return Err(MapfParseError::invalid(
+            filename,
+            "agent_size",
+            format!("{} is not a valid radius", config.agent_size),
+        ));
+    }
     Ok(config)
 }
 
@@ -317,3 +671,173 @@ pub struct Config {
     /// The time limit for the algorithm.
     pub time_limit: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env, f64::consts::PI, fs};
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{get_heading_cbs_from_files, Graph, MapfParseError};
+
+    use super::{build_grid_graph, parse_inputs};
+
+    /// Parses the given map and scenario, written to temporary files.
+    fn parse(name: &str, map: &str, scenario: &str) -> Result<(), MapfParseError> {
+        let map_file = env::temp_dir().join(format!("caboose_{}_map.xml", name));
+        let task_file = env::temp_dir().join(format!("caboose_{}_task.xml", name));
+        fs::write(&map_file, map).unwrap();
+        fs::write(&task_file, scenario).unwrap();
+        parse_inputs(
+            map_file.to_str().unwrap(),
+            task_file.to_str().unwrap(),
+            "resources/config/config-2.xml",
+            usize::MAX,
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let grid =
+            "<root><map><width>2</width><height>1</height><grid><row>0 1</row></grid></map></root>";
+        let graph = |coords: &str, target: &str| {
+            format!(
+                "<graphml><graph><node id=\"n0\"><data key=\"k\">{}</data></node>\
+                <edge source=\"n0\" target=\"{}\"/></graph></graphml>",
+                coords, target
+            )
+        };
+
+        assert!(parse(
+            "grid",
+            grid,
+            "<root><agent start_i=\"0\" start_j=\"0\" goal_i=\"0\" goal_j=\"0\"/></root>"
+        )
+        .is_ok());
+        assert!(matches!(
+            parse(
+                "obstacle",
+                grid,
+                "<root><agent start_i=\"0\" start_j=\"1\" goal_i=\"0\" goal_j=\"0\"/></root>"
+            ),
+            Err(MapfParseError::BlockedCell { cell: (0, 1), .. })
+        ));
+        assert!(matches!(
+            parse(
+                "outside",
+                grid,
+                "<root><agent start_i=\"0\" start_j=\"0\" goal_i=\"3\" goal_j=\"0\"/></root>"
+            ),
+            Err(MapfParseError::BlockedCell { cell: (3, 0), .. })
+        ));
+        assert!(matches!(
+            parse(
+                "missing",
+                grid,
+                "<root><agent start_i=\"0\" goal_i=\"0\" goal_j=\"0\"/></root>"
+            ),
+            Err(MapfParseError::Invalid { .. })
+        ));
+
+        let agent = "<root><agent start_id=\"0\" goal_id=\"0\"/></root>";
+        assert!(parse("graph", &graph("0,0", "n0"), agent).is_ok());
+        assert!(matches!(
+            parse("edge", &graph("0,0", "n1"), agent),
+            Err(MapfParseError::UnknownNode { node, .. }) if node == "n1"
+        ));
+        assert!(matches!(
+            parse("agent", &graph("0,0", "n0"), "<root><agent start_id=\"0\" goal_id=\"2\"/></root>"),
+            Err(MapfParseError::UnknownNode { node, .. }) if node == "n2"
+        ));
+        assert!(matches!(
+            parse("coords", &graph("0;0", "n0"), agent),
+            Err(MapfParseError::Invalid { element, .. }) if element == "node n0"
+        ));
+        assert!(matches!(
+            parse("dimension", &graph("0,0,0", "n0"), agent),
+            Err(MapfParseError::Invalid { .. })
+        ));
+        for data in ["0", "-1", "NaN"] {
+            for key in ["weight", "speed_limit", "duration"] {
+                let map = format!(
+                    "<graphml><key id=\"k\" attr.name=\"{}\"/><graph>\
+                    <node id=\"n0\"><data key=\"c\">0,0</data></node>\
+                    <edge source=\"n0\" target=\"n0\"><data key=\"k\">{}</data></edge>\
+                    </graph></graphml>",
+                    key, data
+                );
+                assert!(matches!(
+                    parse("edge_data", &map, agent),
+                    Err(MapfParseError::Invalid { element, .. }) if element == "edge from n0 to n0"
+                ));
+            }
+        }
+        assert!(matches!(
+            parse("syntax", "<root><map>", agent),
+            Err(MapfParseError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_goal_heading() {
+        let map = "<graphml><graph>\
+            <node id=\"n0\"><data key=\"k\">0,0</data></node>\
+            <node id=\"n1\"><data key=\"k\">0,1</data></node>\
+            <node id=\"n2\"><data key=\"k\">1,1</data></node>\
+            <edge source=\"n0\" target=\"n1\"/><edge source=\"n1\" target=\"n0\"/>\
+            <edge source=\"n1\" target=\"n2\"/><edge source=\"n2\" target=\"n1\"/>\
+            </graph></graphml>";
+        let map_file = env::temp_dir().join("caboose_heading_map.xml");
+        let task_file = env::temp_dir().join("caboose_heading_task.xml");
+        fs::write(&map_file, map).unwrap();
+        fs::write(
+            &task_file,
+            "<root><agent start_id=\"0\" goal_id=\"1\"/></root>",
+        )
+        .unwrap();
+
+        let cost = |angular_speed: f64, goal_heading: f64| {
+            get_heading_cbs_from_files(
+                map_file.to_str().unwrap(),
+                task_file.to_str().unwrap(),
+                "resources/config/config-2.xml",
+                1,
+                1,
+                angular_speed,
+                goal_heading,
+            )
+            .map(|(_, mut cbs, config, _)| cbs.solve(&config).unwrap()[0].cost)
+        };
+
+        // The agent arrives facing north, and needs a quarter turn to face east
+        assert_eq!(cost(PI / 2.0, PI / 2.0).unwrap(), OrderedFloat(1.0));
+        assert_eq!(cost(PI / 2.0, 0.0).unwrap(), OrderedFloat(2.0));
+
+        assert!(matches!(
+            cost(0.0, 0.0),
+            Err(MapfParseError::Invalid { element, .. }) if element == "angular_speed"
+        ));
+    }
+
+    #[test]
+    fn test_grid_connectedness() {
+        let empty = vec![vec![0; 3]; 3];
+        let mut blocked = empty.clone();
+        blocked[1][1] = 1;
+
+        for (rows, connectedness, n_edges) in [
+            (&empty, 2, 24),
+            (&empty, 3, 40),
+            (&empty, 4, 56),
+            (&blocked, 2, 16),
+            (&blocked, 3, 16), // Diagonal moves would cut the corners of the obstacle
+        ] {
+            let mut graph = Graph::new();
+            build_grid_graph(&mut graph, 3, 3, rows, connectedness, 0.4).unwrap();
+            assert_eq!(graph.num_edges(), n_edges);
+        }
+
+        assert!(build_grid_graph(&mut Graph::new(), 3, 3, &empty, 6, 0.4).is_none());
+    }
+}
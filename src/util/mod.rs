@@ -1,3 +1,11 @@
+mod benchmark;
+mod ccbs_log;
+mod error;
 mod mapf_info;
+mod movingai;
 
+pub use benchmark::*;
+pub use ccbs_log::*;
+pub use error::*;
 pub use mapf_info::*;
+pub use movingai::*;
@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use ordered_float::OrderedFloat;
+
+use crate::{
+    build_grid_graph, parse_config, read_from_file, Config, Graph, MapfParseError, MyTime,
+    SimpleEdgeData, SimpleNodeData, SimpleState, Task,
+};
+
+/// An entry of a MovingAI scenario file, describing the task of one agent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioEntry {
+    /// The bucket of the entry, which groups tasks of similar difficulty.
+    pub bucket: usize,
+    /// The (x,y) coordinates of the start cell.
+    pub start: (usize, usize),
+    /// The (x,y) coordinates of the goal cell.
+    pub goal: (usize, usize),
+    /// The length of the optimal path from the start to the goal cell, for a single agent.
+    pub optimal_length: f64,
+}
+
+/// Parse a map and a scenario in the MovingAI formats, from <https://movingai.com/benchmarks/formats.html>
+///
+/// # Arguments
+///
+/// * `map_file` - The `.map` file describing the grid
+/// * `scenario_file` - The `.scen` file describing the tasks
+/// * `config_file` - The configuration file
+/// * `buckets` - The buckets of the scenario to consider, or None for all buckets
+/// * `n_agents` - The number of agents to consider, taken in the order of the scenario
+pub fn parse_movingai_inputs(
+    map_file: &str,
+    scenario_file: &str,
+    config_file: &str,
+    buckets: Option<&[usize]>,
+    n_agents: usize,
+) -> Result<
+    (
+        Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        Vec<Arc<Task<SimpleState, MyTime>>>,
+        Config,
+    ),
+    MapfParseError,
+> {
+    let rows = parse_map(map_file, &read_from_file(map_file)?)?;
+    let height = rows.len();
+    let width = rows.first().map_or(0, |row| row.len());
+    let entries = parse_scenario(scenario_file, &read_from_file(scenario_file)?)?;
+    let config = parse_config(config_file)?;
+
+    let mut graph = Graph::new();
+    let grid = build_grid_graph(
+        &mut graph,
+        width,
+        height,
+        &rows,
+        config.connectedness,
+        config.agent_size,
+    )
+    .ok_or_else(|| MapfParseError::invalid(config_file, "connectedness", config.connectedness))?;
+
+    let cell = |(x, y): (usize, usize), element: String| {
+        if x < width && y < height && rows[y][x] == 0 {
+            Ok(SimpleState(grid[y][x]))
+        } else {
+            Err(MapfParseError::BlockedCell {
+                file: scenario_file.to_string(),
+                element,
+                cell: (x, y),
+            })
+        }
+    };
+
+    let mut tasks = Vec::new();
+    for (i, entry) in entries
+        .iter()
+        .filter(|entry| buckets.is_none_or(|buckets| buckets.contains(&entry.bucket)))
+        .take(n_agents)
+        .enumerate()
+    {
+        tasks.push(Arc::new(Task::new(
+            cell(entry.start, format!("start of agent {}", i))?,
+            cell(entry.goal, format!("goal of agent {}", i))?,
+            OrderedFloat(0.0),
+        )));
+    }
+
+    Ok((Arc::new(graph), tasks, config))
+}
+
+/// Parse a scenario file in the MovingAI format.
+pub fn parse_movingai_scenario(filename: &str) -> Result<Vec<ScenarioEntry>, MapfParseError> {
+    parse_scenario(filename, &read_from_file(filename)?)
+}
+
+/// Returns the rows of the grid described in the MovingAI format, for example:
+/// ```text
+/// type octile
+/// height 3
+/// width 5
+/// map
+/// .@@@.
+/// ...@.
+/// .@...
+/// ```
+/// Cells marked with '.', 'G' or 'S' are free (0), and all others are obstacles (1).
+fn parse_map(file: &str, contents: &str) -> Result<Vec<Vec<usize>>, MapfParseError> {
+    let mut lines = contents.lines();
+    let mut width = None;
+    let mut height = None;
+
+    let dimension = |name: &str, value: Option<&str>| {
+        value
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| MapfParseError::invalid(file, name, "expected a positive integer"))
+    };
+
+    for line in lines.by_ref() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("height") => height = Some(dimension("height", tokens.next())?),
+            Some("width") => width = Some(dimension("width", tokens.next())?),
+            Some("map") => break,
+            _ => {}
+        }
+    }
+
+    let missing = |name: &str| MapfParseError::invalid(file, name, "missing value");
+    let width: usize = width.ok_or_else(|| missing("width"))?;
+    let height: usize = height.ok_or_else(|| missing("height"))?;
+
+    let rows = lines
+        .take(height)
+        .map(|line| {
+            line.chars()
+                .take(width)
+                .map(|c| match c {
+                    '.' | 'G' | 'S' => 0,
+                    _ => 1,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    if rows.len() != height || rows.iter().any(|row| row.len() != width) {
+        return Err(MapfParseError::invalid(
+            file,
+            "map",
+            format!("expected {} rows of {} cells", height, width),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Returns the entries of the scenario described in the MovingAI format, for example:
+/// ```text
+/// version 1
+/// 0    maze.map    5    3    0    0    4    0    6.82842712
+/// ```
+/// Each entry gives the bucket, the map, its width and height, the start and goal coordinates,
+/// and the optimal length, separated by tabs (shown as spaces above).
+fn parse_scenario(file: &str, contents: &str) -> Result<Vec<ScenarioEntry>, MapfParseError> {
+    let mut entries = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with("version") {
+            continue;
+        }
+
+        let element = format!("entry on line {}", i + 1);
+        let columns = line.split('\t').collect::<Vec<_>>();
+        if columns.len() < 9 {
+            return Err(MapfParseError::invalid(
+                file,
+                element,
+                format!("expected 9 columns, found {}", columns.len()),
+            ));
+        }
+
+        let value = |column: usize| {
+            columns[column].trim().parse().map_err(|_| {
+                MapfParseError::invalid(file, &element, format!("malformed {:?}", columns[column]))
+            })
+        };
+
+        entries.push(ScenarioEntry {
+            bucket: value(0)?,
+            start: (value(4)?, value(5)?),
+            goal: (value(6)?, value(7)?),
+            optimal_length: columns[8].trim().parse().map_err(|_| {
+                MapfParseError::invalid(file, &element, format!("malformed {:?}", columns[8]))
+            })?,
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_map, parse_scenario};
+
+    #[test]
+    fn test_parse() {
+        let rows = parse_map(
+            "test.map",
+            "type octile\nheight 3\nwidth 5\nmap\n.@@@.\n...@.\n.@T..\n",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], vec![0, 0, 0, 1, 0]);
+        assert_eq!(rows[2], vec![0, 1, 1, 0, 0]);
+
+        let entries = parse_scenario(
+            "test.scen",
+            "version 1\n0\tmaze.map\t5\t3\t0\t0\t4\t0\t6.82842712\n1\tmaze.map\t5\t3\t4\t2\t0\t2\t5\n",
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].bucket, 0);
+        assert_eq!(entries[0].start, (0, 0));
+        assert_eq!(entries[0].goal, (4, 0));
+        assert_eq!(entries[1].bucket, 1);
+        assert_eq!(entries[1].optimal_length, 5.0);
+
+        assert!(parse_map("test.map", "type octile\nheight 3\nwidth 5\nmap\n.@@@.\n").is_err());
+    }
+}
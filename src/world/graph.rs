@@ -1,9 +1,11 @@
 /// A directed graph node id.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphNodeId(pub usize);
 
 /// A directed graph edge id.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphEdgeId(pub usize);
 
 /// Definition of a directed graph node.
@@ -0,0 +1,427 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use tuple::{A2, T2};
+
+use crate::{
+    Graph, GraphEdgeId, GraphNodeId, Heuristic, HeuristicBuilder, Move, MyTime, SimpleEdgeData,
+    SimpleNodeData, SimpleState, SimpleWorld, State, Task, TransitionSystem,
+};
+
+/// The tolerance used to decide whether two headings are the same.
+const HEADING_PRECISION: f64 = 1e-9;
+
+/// A world described by a directed weighted graph, in which agents have a heading
+/// and must rotate in place to face an edge before moving along it.
+///
+/// The headings available at each node are the directions of the edges leaving it
+/// and the directions in which agents arrive through the edges entering it.
+pub struct HeadingWorld {
+    world: SimpleWorld,
+    angular_speed: f64,
+    /// The headings (in radians) available at each node.
+    headings: Vec<Vec<f64>>,
+    /// The index of the first state of each node in the action vectors.
+    offsets: Vec<usize>,
+    /// The heading an agent must have to leave through each edge.
+    departure_headings: Vec<usize>,
+    /// The heading an agent has when arriving through each edge.
+    arrival_headings: Vec<usize>,
+    actions: Vec<Vec<HeadingAction>>,
+    reverse_actions: Vec<Vec<HeadingAction>>,
+}
+
+impl HeadingWorld {
+    /// Creates a new heading-aware world.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The graph representing the world.
+    /// * `agent_size` - The radius of the agents.
+    /// * `angular_speed` - The angular speed of the agents when rotating in place, in radians per unit of time.
+    ///
+    /// Panics if the angular speed is not positive.
+    pub fn new(
+        graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        agent_size: f64,
+        angular_speed: f64,
+    ) -> Self {
+        assert!(
+            angular_speed > 0.0,
+            "the angular speed must be positive, got {}",
+            angular_speed
+        );
+
+        let mut headings = vec![vec![]; graph.num_nodes()];
+        let mut departure_headings = vec![0; graph.num_edges()];
+        let mut arrival_headings = vec![0; graph.num_edges()];
+
+        for edge_id in 0..graph.num_edges() {
+            let edge = graph.get_edge(GraphEdgeId(edge_id));
+            let from = graph.get_node(edge.from).data;
+            let to = graph.get_node(edge.to).data;
+            let angle = (to.y - from.y).atan2(to.x - from.x);
+            departure_headings[edge_id] = Self::insert_heading(&mut headings[edge.from.0], angle);
+            arrival_headings[edge_id] = Self::insert_heading(&mut headings[edge.to.0], angle);
+        }
+
+        let mut offsets = Vec::with_capacity(graph.num_nodes());
+        let mut num_states = 0;
+        for node_headings in headings.iter_mut() {
+            if node_headings.is_empty() {
+                // Isolated node, agents can only wait there
+                node_headings.push(0.0);
+            }
+            offsets.push(num_states);
+            num_states += node_headings.len();
+        }
+
+        let mut actions = vec![vec![]; num_states];
+        let mut reverse_actions = vec![vec![]; num_states];
+
+        for (node, node_headings) in headings.iter().enumerate() {
+            for from in 0..node_headings.len() {
+                for to in 0..node_headings.len() {
+                    if from != to {
+                        actions[offsets[node] + from].push(HeadingAction::Rotate(from, to));
+                        reverse_actions[offsets[node] + to].push(HeadingAction::Rotate(from, to));
+                    }
+                }
+            }
+        }
+
+        for edge_id in 0..graph.num_edges() {
+            let edge = graph.get_edge(GraphEdgeId(edge_id));
+            actions[offsets[edge.from.0] + departure_headings[edge_id]]
+                .push(HeadingAction::Move(GraphEdgeId(edge_id)));
+            reverse_actions[offsets[edge.to.0] + arrival_headings[edge_id]]
+                .push(HeadingAction::Move(GraphEdgeId(edge_id)));
+        }
+
+        HeadingWorld {
+            world: SimpleWorld::new(graph, agent_size),
+            angular_speed,
+            headings,
+            offsets,
+            departure_headings,
+            arrival_headings,
+            actions,
+            reverse_actions,
+        }
+    }
+
+    /// Returns the index of the given heading in the list, adding it if necessary.
+    fn insert_heading(headings: &mut Vec<f64>, angle: f64) -> usize {
+        if let Some(index) = headings
+            .iter()
+            .position(|heading| Self::angle_between(*heading, angle) < HEADING_PRECISION)
+        {
+            index
+        } else {
+            headings.push(angle);
+            headings.len() - 1
+        }
+    }
+
+    /// Returns the smallest angle between two headings, in [0, PI].
+    fn angle_between(from: f64, to: f64) -> f64 {
+        let delta = (to - from).rem_euclid(2.0 * PI);
+        delta.min(2.0 * PI - delta)
+    }
+
+    fn index(&self, state: &HeadingState) -> usize {
+        self.offsets[state.node.0] + state.heading
+    }
+
+    fn rotation_time(&self, node: GraphNodeId, from: usize, to: usize) -> MyTime {
+        let headings = &self.headings[node.0];
+        (Self::angle_between(headings[from], headings[to]) / self.angular_speed).into()
+    }
+
+    /// Returns the headings (in radians) available at the given node.
+    pub fn get_headings(&self, node: GraphNodeId) -> &[f64] {
+        &self.headings[node.0]
+    }
+
+    /// Returns the state at the given node whose heading is the closest to the given angle.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node of the state.
+    /// * `angle` - The desired heading, in radians.
+    pub fn get_state(&self, node: GraphNodeId, angle: f64) -> HeadingState {
+        let heading = self.headings[node.0]
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                Self::angle_between(**a, angle).total_cmp(&Self::angle_between(**b, angle))
+            })
+            .map(|(heading, _)| heading)
+            .unwrap();
+        HeadingState { node, heading }
+    }
+}
+
+/// A state in the heading-aware world, described by a node and the index of a heading available at that node.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeadingState {
+    /// The node where the agent is.
+    pub node: GraphNodeId,
+    /// The index of the heading of the agent, among the headings available at the node.
+    pub heading: usize,
+}
+
+impl State for HeadingState {
+    fn is_equivalent(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// An action in the heading-aware world.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeadingAction {
+    /// Move along the given edge, which must be aligned with the current heading.
+    Move(GraphEdgeId),
+    /// Rotate in place from the first heading to the second one.
+    Rotate(usize, usize),
+}
+
+impl TransitionSystem<HeadingState, HeadingAction, MyTime, MyTime> for HeadingWorld {
+    fn actions_from(&self, state: &HeadingState) -> std::slice::Iter<'_, HeadingAction> {
+        self.actions[self.index(state)].iter()
+    }
+
+    fn transition(&self, state: &HeadingState, action: &HeadingAction) -> HeadingState {
+        match action {
+            HeadingAction::Move(edge) => HeadingState {
+                node: self.world.graph().get_edge(*edge).to,
+                heading: self.arrival_headings[edge.0],
+            },
+            HeadingAction::Rotate(_, to) => HeadingState {
+                node: state.node,
+                heading: *to,
+            },
+        }
+    }
+
+    fn transition_cost(&self, state: &HeadingState, action: &HeadingAction) -> MyTime {
+        match action {
+            HeadingAction::Move(edge) => self.world.time(*edge),
+            HeadingAction::Rotate(from, to) => self.rotation_time(state.node, *from, *to),
+        }
+    }
+
+    fn reverse_actions_from(&self, state: &HeadingState) -> std::slice::Iter<'_, HeadingAction> {
+        self.reverse_actions[self.index(state)].iter()
+    }
+
+    fn reverse_transition(&self, state: &HeadingState, action: &HeadingAction) -> HeadingState {
+        match action {
+            HeadingAction::Move(edge) => HeadingState {
+                node: self.world.graph().get_edge(*edge).from,
+                heading: self.departure_headings[edge.0],
+            },
+            HeadingAction::Rotate(from, _) => HeadingState {
+                node: state.node,
+                heading: *from,
+            },
+        }
+    }
+
+    fn reverse_transition_cost(&self, state: &HeadingState, action: &HeadingAction) -> MyTime {
+        match action {
+            HeadingAction::Move(edge) => self.world.time(*edge),
+            HeadingAction::Rotate(from, to) => self.rotation_time(state.node, *from, *to),
+        }
+    }
+
+    fn can_wait_at(&self, state: &HeadingState) -> bool {
+        self.world.can_wait_at(&SimpleState(state.node))
+    }
+
+    fn max_waiting_time(&self, state: &HeadingState) -> Option<MyTime> {
+        self.world.max_waiting_time(&SimpleState(state.node))
+    }
+
+    fn conflict(&self, moves: A2<&Move<HeadingState, HeadingAction, MyTime, MyTime>>) -> bool {
+        // An agent rotating in place occupies the same space as a waiting agent
+        self.world.collide(T2(
+            (moves[0].from.node, moves[0].to.node, &moves[0].interval),
+            (moves[1].from.node, moves[1].to.node, &moves[1].interval),
+        ))
+    }
+}
+
+/// A heuristic that returns the time to connect two vertices of the graph in straight line,
+/// ignoring the rotations.
+pub struct HeadingHeuristic {
+    transition_system: Arc<HeadingWorld>,
+    goal_state: HeadingState,
+}
+
+impl HeadingHeuristic {
+    /// Creates a new heading heuristic.
+    ///
+    /// # Arguments
+    ///
+    /// * `transition_system` - The transition system in which the agent navigates.
+    /// * `task` - The task to solve.
+    pub fn new(
+        transition_system: Arc<HeadingWorld>,
+        task: Arc<Task<HeadingState, MyTime>>,
+    ) -> Self {
+        HeadingHeuristic {
+            transition_system,
+            goal_state: task.goal_state.clone(),
+        }
+    }
+}
+
+impl Heuristic<HeadingWorld, HeadingState, HeadingAction, MyTime, MyTime> for HeadingHeuristic {
+    fn get_heuristic(&self, state: &HeadingState) -> Option<MyTime> {
+        Some(
+            self.transition_system
+                .world
+                .time_between(state.node, self.goal_state.node),
+        )
+    }
+}
+
+impl HeuristicBuilder<HeadingWorld, HeadingState, HeadingAction, MyTime, MyTime>
+    for HeadingHeuristic
+{
+    fn build(transition_system: Arc<HeadingWorld>, task: Arc<Task<HeadingState, MyTime>>) -> Self {
+        Self::new(transition_system, task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use ordered_float::OrderedFloat;
+    use tuple::T2;
+
+    use crate::{
+        simple_graph, CbsConfig, ConflictBasedSearch, GraphEdgeId, GraphNodeId, HeadingAction,
+        HeadingHeuristic, HeadingState, HeadingWorld, Interval, Move, MyTime, Task,
+        TransitionSystem,
+    };
+
+    #[test]
+    fn test_rotations() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = HeadingWorld::new(graph, 0.4, PI / 2.0);
+
+        // Corner node with two neighbors, hence two departure and two arrival headings
+        assert_eq!(transition_system.get_headings(GraphNodeId(0)).len(), 4);
+
+        let east = transition_system.get_state(GraphNodeId(0), 0.0);
+        let north = transition_system.get_state(GraphNodeId(0), PI / 2.0);
+        let west = transition_system.get_state(GraphNodeId(0), PI);
+
+        let rotation = HeadingAction::Rotate(east.heading, north.heading);
+        assert_eq!(transition_system.transition(&east, &rotation), north);
+        assert_eq!(
+            transition_system.transition_cost(&east, &rotation),
+            OrderedFloat(1.0)
+        );
+        assert_eq!(
+            transition_system.reverse_transition(&north, &rotation),
+            east
+        );
+
+        let rotation = HeadingAction::Rotate(east.heading, west.heading);
+        assert_eq!(
+            transition_system.transition_cost(&east, &rotation),
+            OrderedFloat(2.0)
+        );
+
+        // Moving is only possible along the current heading
+        for action in transition_system.actions_from(&east) {
+            if let HeadingAction::Move(edge) = action {
+                let next = transition_system.transition(&east, action);
+                assert_eq!(next.node, GraphNodeId(size));
+                assert_eq!(
+                    transition_system.transition_cost(&east, action),
+                    OrderedFloat(1.0)
+                );
+                assert_eq!(transition_system.reverse_transition(&next, action), east);
+                assert_eq!(*edge, GraphEdgeId(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_conflict() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = HeadingWorld::new(graph, 0.4, PI / 2.0);
+
+        let east = transition_system.get_state(GraphNodeId(size), 0.0);
+        let north = transition_system.get_state(GraphNodeId(size), PI / 2.0);
+        let rotation = Move::new(
+            0,
+            east.clone(),
+            north.clone(),
+            Some(HeadingAction::Rotate(east.heading, north.heading)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(1.0)),
+        );
+
+        let from = transition_system.get_state(GraphNodeId(0), 0.0);
+        let to = transition_system.get_state(GraphNodeId(size), 0.0);
+        let crossing = Move::new(
+            1,
+            from,
+            to,
+            Some(HeadingAction::Move(GraphEdgeId(1))),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(1.0)),
+        );
+
+        assert!(transition_system.conflict(T2(&rotation, &crossing)));
+    }
+
+    #[test]
+    fn test_cbs() {
+        let size = 10;
+        let graph = simple_graph(size);
+        let transition_system = Arc::new(HeadingWorld::new(graph, 0.4, PI / 2.0));
+
+        let tasks = vec![
+            Arc::new(Task::new(
+                transition_system.get_state(GraphNodeId(0), PI / 2.0),
+                transition_system.get_state(GraphNodeId(size - 1), PI / 2.0),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                transition_system.get_state(GraphNodeId(size), PI / 2.0),
+                transition_system.get_state(GraphNodeId(size * (size - 1)), 0.0),
+                OrderedFloat(0.0),
+            )),
+        ];
+
+        let config: CbsConfig<
+            HeadingWorld,
+            HeadingState,
+            HeadingAction,
+            MyTime,
+            MyTime,
+            HeadingHeuristic,
+        > = CbsConfig::new(
+            transition_system.clone(),
+            tasks,
+            OrderedFloat(1e-6),
+            1,
+            None,
+        );
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        assert_eq!(solutions[0].cost, OrderedFloat(9.0));
+        // One quarter turn to face east, then straight ahead
+        assert_eq!(solutions[1].cost, OrderedFloat(9.0));
+    }
+}
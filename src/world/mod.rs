@@ -1,5 +1,7 @@
 mod graph;
+mod heading;
 mod simple;
 
 pub use graph::*;
+pub use heading::*;
 pub use simple::*;
@@ -6,26 +6,81 @@ use ncollide2d::{
     shape::Ball,
 };
 use ordered_float::OrderedFloat;
-use tuple::A2;
+use tuple::{A2, T2};
 
 use crate::{
-    Graph, GraphEdgeId, GraphNodeId, Heuristic, HeuristicBuilder, LimitValues, Move, State, Task,
-    TransitionSystem,
+    Graph, GraphEdgeId, GraphNodeId, Heuristic, HeuristicBuilder, Interval, LimitValues, Move,
+    State, Task, TransitionSystem,
 };
 
 /// A wrapper around f64 that implements Ord and LimitValues.
 pub type MyTime = OrderedFloat<f64>;
 
-/// The (x,y) coordinates of a node in the graph.
-pub type SimpleNodeData = (f64, f64);
+/// The data associated with a node of the graph: its coordinates and the rules for waiting at it.
+/// Agents can always stay at their goal state once they have reached it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleNodeData {
+    /// The x coordinate of the node.
+    pub x: f64,
+    /// The y coordinate of the node.
+    pub y: f64,
+    /// Whether agents are forbidden to wait at the node.
+    pub no_wait: bool,
+    /// The maximum time agents can wait at the node, if any.
+    pub max_dwell: Option<f64>,
+}
+
+impl From<(f64, f64)> for SimpleNodeData {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self {
+            x,
+            y,
+            no_wait: false,
+            max_dwell: None,
+        }
+    }
+}
+
+/// The data associated with an edge of the graph, which determines the time needed to traverse it.
+/// Agents move at unit speed by default, so traversing an edge takes as long as its length.
+/// The weight, the speed limit and the duration must be positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleEdgeData {
+    /// A factor applied to the time needed to traverse the edge.
+    pub weight: f64,
+    /// The maximum speed allowed on the edge, if any.
+    pub speed_limit: Option<f64>,
+    /// The time needed to traverse the edge, if known, which overrides the weight and the speed limit.
+    pub duration: Option<f64>,
+}
 
-/// The distance between two nodes in the graph.
-pub type SimpleEdgeData = f64;
+impl Default for SimpleEdgeData {
+    fn default() -> Self {
+        Self {
+            weight: 1.0,
+            speed_limit: None,
+            duration: None,
+        }
+    }
+}
+
+impl From<f64> for SimpleEdgeData {
+    fn from(weight: f64) -> Self {
+        Self {
+            weight,
+            ..Default::default()
+        }
+    }
+}
 
 /// A world simply described by a directed weighted graph
 pub struct SimpleWorld {
     graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
     ball: Ball<f64>,
+    /// The smallest ratio between the time needed to traverse an edge and its length.
+    min_time_ratio: f64,
 }
 
 impl SimpleWorld {
@@ -36,50 +91,105 @@ impl SimpleWorld {
     /// * `graph` - The graph representing the world.
     /// * `agent_size` - The radius of the agents.
     pub fn new(graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>, agent_size: f64) -> Self {
-        SimpleWorld {
+        let mut world = SimpleWorld {
             graph,
             ball: Ball { radius: agent_size },
+            min_time_ratio: 1.0,
+        };
+
+        world.min_time_ratio = (0..world.graph.num_edges())
+            .map(GraphEdgeId)
+            .filter_map(|edge| {
+                let data = world.graph.get_edge(edge);
+                let distance = world.distance(data.from, data.to);
+                (distance > 0.0).then(|| world.time(edge).0 / distance)
+            })
+            .fold(f64::INFINITY, f64::min);
+        if !world.min_time_ratio.is_finite() {
+            world.min_time_ratio = 1.0;
         }
+
+        world
     }
 
-    fn time_between(&self, from: GraphNodeId, to: GraphNodeId) -> MyTime {
+    pub(crate) fn graph(&self) -> &Graph<SimpleNodeData, SimpleEdgeData> {
+        &self.graph
+    }
+
+    fn distance(&self, from: GraphNodeId, to: GraphNodeId) -> f64 {
         let from = self.graph.get_node(from);
         let to = self.graph.get_node(to);
-        let dx = to.data.0 - from.data.0;
-        let dy = to.data.1 - from.data.1;
-        (dx * dx + dy * dy).sqrt().into()
+        let dx = to.data.x - from.data.x;
+        let dy = to.data.y - from.data.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Returns a lower bound on the time needed to go from one node to another.
+    pub(crate) fn time_between(&self, from: GraphNodeId, to: GraphNodeId) -> MyTime {
+        (self.distance(from, to) * self.min_time_ratio).into()
     }
 
-    fn time(&self, edge: GraphEdgeId) -> MyTime {
+    /// Returns the time needed to traverse the given edge.
+    pub(crate) fn time(&self, edge: GraphEdgeId) -> MyTime {
         let edge = self.graph.get_edge(edge);
-        self.time_between(edge.from, edge.to)
+        if let Some(duration) = edge.data.duration {
+            return duration.into();
+        }
+        let speed = edge.data.speed_limit.unwrap_or(1.0).min(1.0);
+        (self.distance(edge.from, edge.to) * edge.data.weight / speed).into()
     }
 
     fn get_center_and_vel(
         &self,
-        m: &Move<SimpleState, GraphEdgeId, MyTime, MyTime>,
+        from: GraphNodeId,
+        to: GraphNodeId,
+        interval: &Interval<MyTime, MyTime>,
         initial_time: &MyTime,
     ) -> (Point2<f64>, Vector2<f64>) {
-        let interval = &m.interval;
-        let from = self.graph.get_node(m.from.0).data;
-        let to = self.graph.get_node(m.to.0).data;
+        let from = self.graph.get_node(from).data;
+        let to = self.graph.get_node(to).data;
 
-        let d_x = to.0 - from.0;
-        let d_y = to.1 - from.1;
+        let d_x = to.x - from.x;
+        let d_y = to.y - from.y;
         let d_t = interval.end.0 - interval.start.0;
         let vel_x = d_x / d_t;
         let vel_y = d_y / d_t;
 
         let pre_d_t = initial_time.0 - interval.start.0;
 
-        let center_x = from.0 + vel_x * pre_d_t;
-        let center_y = from.1 + vel_y * pre_d_t;
+        let center_x = from.x + vel_x * pre_d_t;
+        let center_y = from.y + vel_y * pre_d_t;
 
         (Point2::new(center_x, center_y), Vector2::new(vel_x, vel_y))
     }
+
+    /// Returns true if two agents going from one node to another during the given intervals collide.
+    ///
+    /// # Arguments
+    ///
+    /// * `segments` - The node an agent departs from, the node it arrives at, and the interval of the motion.
+    pub(crate) fn collide(
+        &self,
+        segments: A2<(GraphNodeId, GraphNodeId, &Interval<MyTime, MyTime>)>,
+    ) -> bool {
+        let initial_time = segments[0].2.start.max(segments[1].2.start);
+        let max_time = segments[0].2.end.min(segments[1].2.end) - initial_time;
+
+        let (center1, vel1) =
+            self.get_center_and_vel(segments[0].0, segments[0].1, segments[0].2, &initial_time);
+        let (center2, vel2) =
+            self.get_center_and_vel(segments[1].0, segments[1].1, segments[1].2, &initial_time);
+
+        let toi = query::time_of_impact_ball_ball(
+            &center1, &vel1, &self.ball, &center2, &vel2, &self.ball, max_time.0, 0.0,
+        );
+
+        toi.is_some()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A state in the simple world, simply represented by a GraphNodeId.
 pub struct SimpleState(pub GraphNodeId);
 
@@ -114,22 +224,23 @@ impl TransitionSystem<SimpleState, GraphEdgeId, MyTime, MyTime> for SimpleWorld
         self.time(*action)
     }
 
-    fn can_wait_at(&self, _state: &SimpleState) -> bool {
-        true
+    fn can_wait_at(&self, state: &SimpleState) -> bool {
+        !self.graph.get_node(state.0).data.no_wait
     }
 
-    fn conflict(&self, moves: A2<&Move<SimpleState, GraphEdgeId, MyTime, MyTime>>) -> bool {
-        let initial_time = moves[0].interval.start.max(moves[1].interval.start);
-        let max_time = moves[0].interval.end.min(moves[1].interval.end) - initial_time;
-
-        let (center1, vel1) = self.get_center_and_vel(moves[0], &initial_time);
-        let (center2, vel2) = self.get_center_and_vel(moves[1], &initial_time);
-
-        let toi = query::time_of_impact_ball_ball(
-            &center1, &vel1, &self.ball, &center2, &vel2, &self.ball, max_time.0, 0.0,
-        );
+    fn max_waiting_time(&self, state: &SimpleState) -> Option<MyTime> {
+        self.graph
+            .get_node(state.0)
+            .data
+            .max_dwell
+            .map(OrderedFloat)
+    }
 
-        toi.is_some()
+    fn conflict(&self, moves: A2<&Move<SimpleState, GraphEdgeId, MyTime, MyTime>>) -> bool {
+        self.collide(T2(
+            (moves[0].from.0, moves[0].to.0, &moves[0].interval),
+            (moves[1].from.0, moves[1].to.0, &moves[1].interval),
+        ))
     }
 }
 
@@ -183,23 +294,23 @@ pub fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
     let mut graph = Graph::new();
     for x in 0..size {
         for y in 0..size {
-            graph.add_node((x as f64, y as f64));
+            graph.add_node((x as f64, y as f64).into());
         }
     }
     for x in 0..size {
         for y in 0..size {
             let node_id = GraphNodeId(x + y * size);
             if x > 0 {
-                graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
+                graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0.into());
             }
             if y > 0 {
-                graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
+                graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0.into());
             }
             if x < size - 1 {
-                graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
+                graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0.into());
             }
             if y < size - 1 {
-                graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
+                graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0.into());
             }
         }
     }
@@ -214,8 +325,8 @@ mod tests {
     use tuple::T2;
 
     use crate::{
-        simple_graph, GraphEdgeId, GraphNodeId, Interval, Move, SimpleState, SimpleWorld,
-        TransitionSystem,
+        simple_graph, Graph, GraphEdgeId, GraphNodeId, Heuristic, Interval, Move, SimpleEdgeData,
+        SimpleHeuristic, SimpleState, SimpleWorld, Task, TransitionSystem,
     };
 
     #[test]
@@ -244,4 +355,55 @@ mod tests {
 
         assert!(transition_system.conflict(T2(&move1, &move2)));
     }
+
+    #[test]
+    fn test_edge_data() {
+        let mut graph = Graph::new();
+        let nodes = (0..3)
+            .map(|x| graph.add_node((x as f64, 0.0).into()))
+            .collect::<Vec<_>>();
+        let weighted = graph.add_edge(nodes[0], nodes[1], 2.0.into());
+        let limited = graph.add_edge(
+            nodes[1],
+            nodes[2],
+            SimpleEdgeData {
+                speed_limit: Some(0.5),
+                ..Default::default()
+            },
+        );
+        let timed = graph.add_edge(
+            nodes[0],
+            nodes[2],
+            SimpleEdgeData {
+                duration: Some(1.0),
+                ..Default::default()
+            },
+        );
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let state = SimpleState(nodes[0]);
+        assert_eq!(
+            transition_system.transition_cost(&state, &weighted),
+            OrderedFloat(2.0)
+        );
+        assert_eq!(
+            transition_system.transition_cost(&SimpleState(nodes[1]), &limited),
+            OrderedFloat(2.0)
+        );
+        assert_eq!(
+            transition_system.transition_cost(&state, &timed),
+            OrderedFloat(1.0)
+        );
+
+        // The heuristic must not overestimate the time of the fastest edge
+        let heuristic = SimpleHeuristic::new(
+            transition_system,
+            Arc::new(Task::new(
+                SimpleState(nodes[0]),
+                SimpleState(nodes[2]),
+                OrderedFloat(0.0),
+            )),
+        );
+        assert!(heuristic.get_heuristic(&state).unwrap() <= OrderedFloat(1.0));
+    }
 }